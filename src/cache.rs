@@ -0,0 +1,86 @@
+use std::{collections::HashMap, time::Duration};
+
+use prometheus::{Error, IntCounterVec, Opts, Registry};
+use tokio::{sync::Mutex, time::Instant};
+
+use crate::utils::CacheConfig;
+
+/// Cache of raw info API responses, keyed by the serialized request.
+#[derive(Debug)]
+pub struct ResponseCache {
+    cache_config: CacheConfig,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    hits_total: IntCounterVec,
+    misses_total: IntCounterVec,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    body: String,
+    expires_at: Instant,
+}
+
+impl ResponseCache {
+    pub fn new(cache_config: &CacheConfig) -> Result<Self, Error> {
+        Ok(Self {
+            cache_config: cache_config.clone(),
+            entries: Mutex::new(HashMap::new()),
+            hits_total: IntCounterVec::new(
+                Opts::new(
+                    "info_cache_hits_total",
+                    "The number of info requests which were served from the cache",
+                ),
+                &["request"],
+            )?,
+            misses_total: IntCounterVec::new(
+                Opts::new(
+                    "info_cache_misses_total",
+                    "The number of cacheable info requests which were sent upstream",
+                ),
+                &["request"],
+            )?,
+        })
+    }
+
+    pub fn register(&self, registry: &Registry) -> Result<(), Error> {
+        registry.register(Box::new(self.hits_total.clone()))?;
+        registry.register(Box::new(self.misses_total.clone()))?;
+
+        Ok(())
+    }
+
+    /// The configured TTL of the request type, `None` if its responses aren't cached.
+    pub fn ttl(&self, request_type: &str) -> Option<Duration> {
+        match self.cache_config.ttl_secs.get(request_type) {
+            Some(ttl_secs) if *ttl_secs > 0 => Some(Duration::from_secs(*ttl_secs)),
+            _ => None,
+        }
+    }
+
+    pub async fn get(&self, request_type: &str, key: &str) -> Option<String> {
+        let entries = self.entries.lock().await;
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                self.hits_total.with_label_values(&[request_type]).inc();
+                Some(entry.body.clone())
+            }
+            _ => {
+                self.misses_total.with_label_values(&[request_type]).inc();
+                None
+            }
+        }
+    }
+
+    pub async fn insert(&self, key: String, body: String, ttl: Duration) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().await;
+        entries.retain(|_, entry| entry.expires_at > now);
+        entries.insert(
+            key,
+            CacheEntry {
+                body,
+                expires_at: now + ttl,
+            },
+        );
+    }
+}
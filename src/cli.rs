@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::utils::{Config, read_config};
+
+/// Prometheus exporter for Hyperliquid protocol, vault and account metrics.
+///
+/// Settings are resolved in the following order of precedence: command line flags,
+/// `HYPURR_*` environment variables, the config file and finally the defaults.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path of the config file. Without this flag, a missing `config.toml` is tolerated
+    /// and the config is read from the environment only.
+    #[arg(long, env = "HYPURR_CONFIG", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Address the HTTP server listens on, e.g. `0.0.0.0:3000`.
+    #[arg(long)]
+    pub listen_address: Option<String>,
+
+    /// Path under which the metrics are served, e.g. `/metrics`.
+    #[arg(long)]
+    pub metrics_path: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Validates the config and prints it with the secrets redacted. Exits with a non-zero
+    /// status if the config is invalid.
+    CheckConfig,
+}
+
+impl Cli {
+    /// Reads the config file, applies the environment overrides and finally the flags,
+    /// and validates the result.
+    pub async fn read_config(&self) -> anyhow::Result<Config> {
+        let mut config = match &self.config {
+            Some(path) => read_config(path, true).await?,
+            None => read_config(Path::new("config.toml"), false).await?,
+        };
+
+        if let Some(listen_address) = &self.listen_address {
+            config.listen_address = listen_address.clone();
+        }
+        if let Some(metrics_path) = &self.metrics_path {
+            config.metrics_path = metrics_path.clone();
+        }
+        config.validate()?;
+
+        Ok(config)
+    }
+}
@@ -1,3 +1,6 @@
 pub static MAINNET_INFO_API_URL: &str = "https://api.hyperliquid.xyz/info";
+pub static TESTNET_INFO_API_URL: &str = "https://api.hyperliquid-testnet.xyz/info";
 pub static COINGECKO_HL_API_URL: &str = "https://api.coingecko.com/api/v3/coins/hyperliquid";
+pub static COINGECKO_CACHE_KEY: &str = "coingecko";
 pub static ALCHEMY_API_URL: &str = "https://hyperliquid-mainnet.g.alchemy.com/v2/";
+pub static ALCHEMY_TESTNET_API_URL: &str = "https://hyperliquid-testnet.g.alchemy.com/v2/";
@@ -0,0 +1,80 @@
+use std::{
+    num::{ParseFloatError, ParseIntError},
+    sync::Arc,
+};
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+/// Maximum number of characters of an upstream error body which are kept in an error.
+const MAX_BODY_LEN: usize = 512;
+
+#[derive(Debug, Error)]
+pub enum ExporterError {
+    #[error("Request to {upstream} failed: {source}")]
+    Transport {
+        upstream: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("{upstream} responded with status {status}: {body}")]
+    HttpStatus {
+        upstream: String,
+        status: StatusCode,
+        body: String,
+    },
+    #[error("Failed to decode the response of {upstream} at `{path}`: {source}")]
+    JsonDecode {
+        upstream: String,
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Missing field: {0}")]
+    MissingField(String),
+    #[error("Invalid number: {0}")]
+    InvalidNumber(String),
+    /// The error of an upstream call which was shared by coalesced requests.
+    #[error("{0}")]
+    Shared(Arc<ExporterError>),
+}
+
+impl ExporterError {
+    /// A short, stable name of the error kind which is used as metric label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExporterError::Transport { .. } => "transport",
+            ExporterError::HttpStatus { .. } => "http_status",
+            ExporterError::JsonDecode { .. } => "json_decode",
+            ExporterError::MissingField(_) => "missing_field",
+            ExporterError::InvalidNumber(_) => "invalid_number",
+            ExporterError::Shared(error) => error.kind(),
+        }
+    }
+
+    pub fn http_status(upstream: &str, status: StatusCode, body: &str) -> Self {
+        let mut body = body.trim().to_string();
+        if let Some((index, _)) = body.char_indices().nth(MAX_BODY_LEN) {
+            body.truncate(index);
+            body.push_str("...");
+        }
+
+        ExporterError::HttpStatus {
+            upstream: upstream.to_string(),
+            status,
+            body,
+        }
+    }
+}
+
+impl From<ParseFloatError> for ExporterError {
+    fn from(e: ParseFloatError) -> Self {
+        ExporterError::InvalidNumber(e.to_string())
+    }
+}
+
+impl From<ParseIntError> for ExporterError {
+    fn from(e: ParseIntError) -> Self {
+        ExporterError::InvalidNumber(e.to_string())
+    }
+}
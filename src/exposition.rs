@@ -0,0 +1,328 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use prometheus::{
+    Encoder, PROTOBUF_FORMAT, ProtobufEncoder, TEXT_FORMAT, TextEncoder,
+    proto::{LabelPair, Metric, MetricFamily, MetricType},
+};
+
+pub const OPENMETRICS_FORMAT: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The units which are announced in OpenMetrics if a metric name ends with them.
+const UNITS: [&str; 3] = ["seconds", "bytes", "ratio"];
+
+/// The format the metrics are served in, negotiated by the `Accept` header of the scrape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExpositionFormat {
+    Text,
+    OpenMetrics,
+    Protobuf,
+}
+
+impl ExpositionFormat {
+    /// Picks the format with the highest quality the client accepts, like the Prometheus
+    /// client libraries do. Without a supported format in the header, the metrics are
+    /// served in the text format.
+    pub fn negotiate(accept: Option<&str>) -> Self {
+        let mut negotiated = (ExpositionFormat::Text, 0.0);
+        for media_range in accept.unwrap_or_default().split(',') {
+            let mut parts = media_range.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let params: HashMap<String, &str> = parts
+                .filter_map(|param| param.split_once('='))
+                .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim()))
+                .collect();
+            let quality = params
+                .get("q")
+                .and_then(|quality| quality.parse::<f64>().ok())
+                .unwrap_or(1.0);
+
+            let format = match media_type.as_str() {
+                "application/openmetrics-text"
+                    if params
+                        .get("version")
+                        .is_none_or(|version| ["1.0.0", "0.0.1"].contains(version)) =>
+                {
+                    ExpositionFormat::OpenMetrics
+                }
+                "application/vnd.google.protobuf"
+                    if params.get("proto") == Some(&"io.prometheus.client.MetricFamily")
+                        && params.get("encoding") == Some(&"delimited") =>
+                {
+                    ExpositionFormat::Protobuf
+                }
+                "text/plain" | "text/*" | "*/*" => ExpositionFormat::Text,
+                _ => continue,
+            };
+            if quality > negotiated.1 {
+                negotiated = (format, quality);
+            }
+        }
+
+        negotiated.0
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExpositionFormat::Text => TEXT_FORMAT,
+            ExpositionFormat::OpenMetrics => OPENMETRICS_FORMAT,
+            ExpositionFormat::Protobuf => PROTOBUF_FORMAT,
+        }
+    }
+
+    /// Encodes the metric families, which are expected to be sorted by name.
+    pub fn encode(
+        &self,
+        metric_families: &[MetricFamily],
+        created_timestamps: &CreatedTimestamps,
+    ) -> Result<Vec<u8>, prometheus::Error> {
+        match self {
+            ExpositionFormat::Text => {
+                let mut buffer = Vec::new();
+                TextEncoder::new().encode(metric_families, &mut buffer)?;
+                Ok(buffer)
+            }
+            ExpositionFormat::OpenMetrics => Ok(encode_open_metrics(
+                metric_families,
+                &created_timestamps.observe(metric_families),
+            )
+            .into_bytes()),
+            ExpositionFormat::Protobuf => {
+                let mut buffer = Vec::new();
+                ProtobufEncoder::new().encode(metric_families, &mut buffer)?;
+                Ok(buffer)
+            }
+        }
+    }
+}
+
+/// Tracks since when the counters, histograms and summaries count, as the `prometheus`
+/// crate doesn't record it. A series which appears or got reset since the last scrape
+/// counts since that scrape, which is the latest time it is known to have started at.
+#[derive(Debug)]
+pub struct CreatedTimestamps {
+    state: Mutex<CreatedState>,
+}
+
+#[derive(Debug)]
+struct CreatedState {
+    last_observed: f64,
+    /// The created timestamp and the last count of each series.
+    series: HashMap<String, (f64, f64)>,
+}
+
+impl Default for CreatedTimestamps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CreatedTimestamps {
+    /// The series which exist at the first scrape count since now, so this should be
+    /// created along with the metrics.
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(CreatedState {
+                last_observed: unix_time(),
+                series: HashMap::new(),
+            }),
+        }
+    }
+
+    fn observe(&self, metric_families: &[MetricFamily]) -> HashMap<String, f64> {
+        let mut state = self.state.lock().unwrap();
+        let now = unix_time();
+
+        let mut series = HashMap::new();
+        for metric_family in metric_families {
+            for metric in metric_family.get_metric() {
+                let count = match metric_family.get_field_type() {
+                    MetricType::COUNTER => metric.get_counter().value(),
+                    MetricType::HISTOGRAM => metric.get_histogram().get_sample_count() as f64,
+                    MetricType::SUMMARY => metric.get_summary().sample_count() as f64,
+                    MetricType::GAUGE | MetricType::UNTYPED => continue,
+                };
+                let key = series_key(metric_family.name(), metric);
+                let created = match state.series.get(&key) {
+                    Some((created, last_count)) if count >= *last_count => *created,
+                    _ => state.last_observed,
+                };
+                series.insert(key, (created, count));
+            }
+        }
+
+        // Series which are gone are dropped, so that they count anew if they reappear.
+        state.series = series;
+        state.last_observed = now;
+
+        state
+            .series
+            .iter()
+            .map(|(key, (created, _))| (key.clone(), *created))
+            .collect()
+    }
+}
+
+/// Encodes the metric families in the OpenMetrics text format. Counters lose their
+/// `_total` suffix in the family name, units are derived from the suffix of the name.
+fn encode_open_metrics(
+    metric_families: &[MetricFamily],
+    created_timestamps: &HashMap<String, f64>,
+) -> String {
+    let mut output = String::new();
+    for metric_family in metric_families {
+        let name = metric_family.name();
+        let metric_type = metric_family.get_field_type();
+        let (family_name, type_name) = match metric_type {
+            MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+            MetricType::GAUGE => (name, "gauge"),
+            MetricType::HISTOGRAM => (name, "histogram"),
+            MetricType::SUMMARY => (name, "summary"),
+            MetricType::UNTYPED => (name, "unknown"),
+        };
+
+        output.push_str(&format!("# TYPE {family_name} {type_name}\n"));
+        if let Some(unit) = UNITS
+            .iter()
+            .find(|unit| family_name.ends_with(&format!("_{unit}")))
+        {
+            output.push_str(&format!("# UNIT {family_name} {unit}\n"));
+        }
+        if !metric_family.help().is_empty() {
+            output.push_str(&format!(
+                "# HELP {family_name} {}\n",
+                escape(metric_family.help())
+            ));
+        }
+
+        for metric in metric_family.get_metric() {
+            let created = created_timestamps
+                .get(&series_key(name, metric))
+                .copied()
+                .unwrap_or_else(unix_time);
+            let mut sample = |suffix: &str, extra_label: Option<(&str, String)>, value: f64| {
+                write_sample(
+                    &mut output,
+                    &format!("{family_name}{suffix}"),
+                    metric,
+                    extra_label,
+                    value,
+                )
+            };
+
+            match metric_type {
+                MetricType::COUNTER => {
+                    sample("_total", None, metric.get_counter().value());
+                    sample("_created", None, created);
+                }
+                MetricType::GAUGE => sample("", None, metric.get_gauge().value()),
+                MetricType::UNTYPED => sample("", None, metric.untyped.value()),
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    let mut inf_seen = false;
+                    for bucket in histogram.get_bucket() {
+                        let upper_bound = bucket.upper_bound();
+                        inf_seen |= upper_bound == f64::INFINITY;
+                        sample(
+                            "_bucket",
+                            Some(("le", format_float(upper_bound))),
+                            bucket.cumulative_count() as f64,
+                        );
+                    }
+                    if !inf_seen {
+                        sample(
+                            "_bucket",
+                            Some(("le", format_float(f64::INFINITY))),
+                            histogram.get_sample_count() as f64,
+                        );
+                    }
+                    sample("_count", None, histogram.get_sample_count() as f64);
+                    sample("_sum", None, histogram.get_sample_sum());
+                    sample("_created", None, created);
+                }
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    for quantile in summary.get_quantile() {
+                        sample(
+                            "",
+                            Some(("quantile", format_float(quantile.quantile()))),
+                            quantile.value(),
+                        );
+                    }
+                    sample("_count", None, summary.sample_count() as f64);
+                    sample("_sum", None, summary.sample_sum());
+                    sample("_created", None, created);
+                }
+            }
+        }
+    }
+    output.push_str("# EOF\n");
+
+    output
+}
+
+fn write_sample(
+    output: &mut String,
+    name: &str,
+    metric: &Metric,
+    extra_label: Option<(&str, String)>,
+    value: f64,
+) {
+    output.push_str(name);
+
+    let labels: Vec<String> = metric
+        .get_label()
+        .iter()
+        .map(|label| (label.name(), label.value().to_string()))
+        .chain(extra_label)
+        .map(|(name, value)| format!("{name}=\"{}\"", escape(&value)))
+        .collect();
+    if !labels.is_empty() {
+        output.push_str(&format!("{{{}}}", labels.join(",")));
+    }
+
+    output.push_str(&format!(" {}", format_float(value)));
+    // OpenMetrics timestamps are in seconds instead of milliseconds.
+    if metric.timestamp_ms() != 0 {
+        output.push_str(&format!(" {}", metric.timestamp_ms() as f64 / 1000.0));
+    }
+    output.push('\n');
+}
+
+fn series_key(name: &str, metric: &Metric) -> String {
+    metric
+        .get_label()
+        .iter()
+        .fold(name.to_string(), |key, label: &LabelPair| {
+            format!("{key}\u{0}{}\u{0}{}", label.name(), label.value())
+        })
+}
+
+fn format_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        format!("{value:?}")
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('\n', r"\n")
+        .replace('"', r#"\""#)
+}
+
+fn unix_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
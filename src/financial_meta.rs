@@ -1,59 +1,98 @@
-use anyhow::bail;
-use reqwest::Client;
+use reqwest::header::HeaderValue;
 
-use crate::consts::COINGECKO_HL_API_URL;
+use crate::{
+    consts::COINGECKO_CACHE_KEY,
+    error::ExporterError,
+    http_client::{HttpClient, decode_json},
+};
 
 pub async fn get_coingecko_data(
+    http_client: &HttpClient,
+    coingecko_api_url: &str,
     coingecko_key: &str,
-) -> anyhow::Result<(f64, i64, i64, i64, f64, f64)> {
-    let http_client = Client::new();
-    let url = COINGECKO_HL_API_URL.to_string();
+) -> Result<(f64, i64, i64, i64, f64, f64), ExporterError> {
+    let url = coingecko_api_url.to_string();
 
-    let response = http_client
-        .get(url)
-        .header("accept", "application/json")
-        .header("x-cg-api-key", coingecko_key)
-        .send()
-        .await?;
-    let json: serde_json::Value = response.json().await?;
+    let mut request = http_client.get(&url).header("accept", "application/json");
+    request = match HeaderValue::from_str(coingecko_key) {
+        Ok(mut coingecko_key) => {
+            coingecko_key.set_sensitive(true);
+            request.header("x-cg-api-key", coingecko_key)
+        }
+        // Invalid keys fail when the request is built.
+        Err(_) => request.header("x-cg-api-key", coingecko_key),
+    };
+
+    // The data is cached like the info API responses, as the rate limits of CoinGecko are
+    // tight and its data only refreshes every minute or so anyway.
+    let cache = http_client.info_cache();
+    let ttl = cache.ttl(COINGECKO_CACHE_KEY);
+    let cache_key = format!("{COINGECKO_CACHE_KEY} {url}");
+    let cached_body = match ttl {
+        Some(_) => cache.get(COINGECKO_CACHE_KEY, &cache_key).await,
+        None => None,
+    };
+    let json: serde_json::Value = match cached_body {
+        Some(body) => decode_json("coingecko", &body)?,
+        None => {
+            let body = http_client.send_text("coingecko", request).await?;
+            let json = decode_json("coingecko", &body)?;
+            if let Some(ttl) = ttl {
+                cache.insert(cache_key, body, ttl).await;
+            }
+            json
+        }
+    };
 
     let hyperliquid_price =
         if let Some(hyperliquid_price) = json["market_data"]["current_price"]["usd"].as_f64() {
             hyperliquid_price
         } else {
-            bail!("Failed to convert the price to f64!");
+            return Err(ExporterError::MissingField(
+                "Failed to convert the price to f64!".to_string(),
+            ));
         };
     let hyperliquid_marketcap =
         if let Some(hyperliquid_marketcap) = json["market_data"]["market_cap"]["usd"].as_i64() {
             hyperliquid_marketcap
         } else {
-            bail!("Failed to convert the marketcap to i64!");
+            return Err(ExporterError::MissingField(
+                "Failed to convert the marketcap to i64!".to_string(),
+            ));
         };
     let hyperliquid_fdv = if let Some(hyperliquid_fdv) =
         json["market_data"]["fully_diluted_valuation"]["usd"].as_i64()
     {
         hyperliquid_fdv
     } else {
-        bail!("Failed to convert the fdv to i64!");
+        return Err(ExporterError::MissingField(
+            "Failed to convert the fdv to i64!".to_string(),
+        ));
     };
     let hyperliquid_tvl =
         if let Some(hyperliquid_tvl) = json["market_data"]["total_value_locked"]["usd"].as_i64() {
             hyperliquid_tvl
         } else {
-            bail!("Failed to convert the tvl to i64!");
+            return Err(ExporterError::MissingField(
+                "Failed to convert the tvl to i64!".to_string(),
+            ));
         };
     let hyperliquid_circulating_supply = if let Some(hyperliquid_circulating_supply) =
         json["market_data"]["circulating_supply"].as_f64()
     {
         hyperliquid_circulating_supply
     } else {
-        bail!("Failed to convert the circulating supply to f64!");
+        return Err(ExporterError::MissingField(
+            "Failed to convert the circulating supply to f64!".to_string(),
+        ));
     };
     let hyperliquid_total_supply =
         if let Some(hyperliquid_total_supply) = json["market_data"]["total_supply"].as_f64() {
             hyperliquid_total_supply
         } else {
-            bail!("Failed to convert the total supply to f64!");
+            return Err(ExporterError::MissingField(
+                "Failed to convert the total supply to f64!".to_string(),
+            ));
         };
 
     Ok((
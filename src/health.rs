@@ -0,0 +1,87 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::{error::ExporterError, utils::Config};
+
+/// Status of a collector as reported by the health endpoints.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CollectorStatus {
+    /// Whether the collector is enabled by the config, e.g. the vault collector needs a
+    /// `vault_address`.
+    pub enabled: bool,
+    /// Whether the collector has refreshed successfully at least once.
+    pub ready: bool,
+    pub last_success: Option<String>,
+    pub last_error: Option<String>,
+    pub last_error_time: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HealthReport {
+    pub status: &'static str,
+    pub collectors: BTreeMap<&'static str, CollectorStatus>,
+}
+
+/// Tracks the outcome of the collector runs for the liveness and readiness endpoints.
+#[derive(Debug, Default)]
+pub struct Health {
+    collectors: Mutex<BTreeMap<&'static str, CollectorStatus>>,
+}
+
+impl Health {
+    pub fn new(config: &Config) -> Self {
+        let health = Self::default();
+        health.configure(config);
+        health
+    }
+
+    /// Enables and disables the collectors according to the config. The status of the
+    /// collectors which stay enabled is kept.
+    pub fn configure(&self, config: &Config) {
+        let enabled_collectors = [
+            ("coingecko", config.coingecko_key.is_some()),
+            ("evm", config.evm_rpc_url().is_some()),
+            ("protocol", true),
+            ("validator", true),
+            ("vault", config.vault_address.is_some()),
+            ("user", config.user_address.is_some()),
+        ];
+
+        let mut collectors = self.collectors.lock().unwrap();
+        for (collector, enabled) in enabled_collectors {
+            collectors.entry(collector).or_default().enabled = enabled;
+        }
+    }
+
+    pub fn record_success(&self, collector: &'static str) {
+        let mut collectors = self.collectors.lock().unwrap();
+        let status = collectors.entry(collector).or_default();
+        status.ready = true;
+        status.last_success = Some(Utc::now().to_rfc3339());
+    }
+
+    pub fn record_error(&self, collector: &'static str, error: &ExporterError) {
+        let mut collectors = self.collectors.lock().unwrap();
+        let status = collectors.entry(collector).or_default();
+        status.last_error = Some(error.to_string());
+        status.last_error_time = Some(Utc::now().to_rfc3339());
+    }
+
+    /// Whether every enabled collector has refreshed successfully at least once.
+    pub fn is_ready(&self) -> bool {
+        self.collectors
+            .lock()
+            .unwrap()
+            .values()
+            .all(|status| !status.enabled || status.ready)
+    }
+
+    pub fn report(&self, status: &'static str) -> HealthReport {
+        HealthReport {
+            status,
+            collectors: self.collectors.lock().unwrap().clone(),
+        }
+    }
+}
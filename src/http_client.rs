@@ -0,0 +1,323 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use prometheus::{Error, IntCounterVec, Opts, Registry};
+use rand::Rng;
+use reqwest::{
+    Client, ClientBuilder, Proxy, RequestBuilder, Response, StatusCode, header::RETRY_AFTER,
+};
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+use crate::{
+    cache::ResponseCache,
+    error::ExporterError,
+    rate_limiter::RateLimiter,
+    single_flight::SingleFlight,
+    transport::{ReqwestTransport, Transport},
+    utils::{CacheConfig, HttpConfig, RateLimitConfig, RetryConfig},
+};
+
+/// HTTP client which is shared by all collectors and retries transient upstream failures.
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+    client: Client,
+    transport: Arc<dyn Transport>,
+    retry_config: RetryConfig,
+    upstream_retries_total: IntCounterVec,
+    info_rate_limiter: Arc<RateLimiter>,
+    info_cache: Arc<ResponseCache>,
+    info_single_flight: Arc<SingleFlight>,
+}
+
+impl HttpClient {
+    pub fn new(
+        http_config: &HttpConfig,
+        retry_config: &RetryConfig,
+        rate_limit_config: &RateLimitConfig,
+        cache_config: &CacheConfig,
+    ) -> anyhow::Result<Self> {
+        let client = client_builder(http_config)?
+            .build()
+            .context("Failed to build the HTTP client")?;
+
+        Self::from_client(client, retry_config, rate_limit_config, cache_config)
+    }
+
+    /// Creates a client which sends its requests with the given reqwest client.
+    pub(crate) fn from_client(
+        client: Client,
+        retry_config: &RetryConfig,
+        rate_limit_config: &RateLimitConfig,
+        cache_config: &CacheConfig,
+    ) -> anyhow::Result<Self> {
+        let transport = Arc::new(ReqwestTransport::new(client.clone()));
+
+        Self::with_transport(
+            client,
+            transport,
+            retry_config,
+            rate_limit_config,
+            cache_config,
+        )
+    }
+
+    /// Creates a client which executes its requests with the given transport, e.g. a
+    /// `FixtureTransport` for offline tests. The `client` is only used to build requests.
+    pub fn with_transport(
+        client: Client,
+        transport: Arc<dyn Transport>,
+        retry_config: &RetryConfig,
+        rate_limit_config: &RateLimitConfig,
+        cache_config: &CacheConfig,
+    ) -> anyhow::Result<Self> {
+        let upstream_retries_total = IntCounterVec::new(
+            Opts::new(
+                "upstream_retries_total",
+                "The number of retried requests to an upstream API",
+            ),
+            &["upstream"],
+        )?;
+
+        Ok(Self {
+            client,
+            transport,
+            retry_config: retry_config.clone(),
+            upstream_retries_total,
+            info_rate_limiter: Arc::new(RateLimiter::new(rate_limit_config)?),
+            info_cache: Arc::new(ResponseCache::new(cache_config)?),
+            info_single_flight: Arc::new(SingleFlight::default()),
+        })
+    }
+
+    pub fn register(&self, registry: &Registry) -> Result<(), Error> {
+        registry.register(Box::new(self.upstream_retries_total.clone()))?;
+        self.info_rate_limiter.register(registry)?;
+        self.info_cache.register(registry)?;
+
+        Ok(())
+    }
+
+    pub fn info_rate_limiter(&self) -> &RateLimiter {
+        &self.info_rate_limiter
+    }
+
+    pub fn info_cache(&self) -> &ResponseCache {
+        &self.info_cache
+    }
+
+    pub fn info_single_flight(&self) -> &SingleFlight {
+        &self.info_single_flight
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    /// Sends the request and retries it with exponential backoff and jitter on transport
+    /// errors and retryable statuses. A `Retry-After` header of a 429 or 503 response takes
+    /// precedence over the backoff. If it asks for a longer delay than `max_delay_ms`, the
+    /// response is returned instead of retrying early.
+    pub async fn send(
+        &self,
+        upstream: &str,
+        request: RequestBuilder,
+    ) -> Result<Response, ExporterError> {
+        self.send_weighted(upstream, request, None).await
+    }
+
+    /// Sends the request like `send`, but acquires the weight from the info rate limiter
+    /// before every attempt, as retries count against the rate limit as well.
+    async fn send_weighted(
+        &self,
+        upstream: &str,
+        request: RequestBuilder,
+        weight: Option<u32>,
+    ) -> Result<Response, ExporterError> {
+        let attempts = self.retry_config.attempts.max(1);
+        let max_delay = Duration::from_millis(self.retry_config.max_delay_ms);
+        // The URL is stripped, as it may contain an API key, e.g. of Alchemy.
+        let transport_error = |source: reqwest::Error| ExporterError::Transport {
+            upstream: upstream.to_string(),
+            source: source.without_url(),
+        };
+
+        let mut attempt = 1;
+        loop {
+            // Requests with a streaming body can't be cloned and are therefore sent only once.
+            let Some(attempt_request) = request.try_clone() else {
+                let request = request.build().map_err(transport_error)?;
+                if let Some(weight) = weight {
+                    self.info_rate_limiter.acquire(weight).await;
+                }
+                return self
+                    .transport
+                    .execute(request)
+                    .await
+                    .map_err(transport_error);
+            };
+            let attempt_request = attempt_request.build().map_err(transport_error)?;
+            if let Some(weight) = weight {
+                self.info_rate_limiter.acquire(weight).await;
+            }
+
+            let delay = match self.transport.execute(attempt_request).await {
+                Ok(response) if attempt < attempts && self.is_retryable(response.status()) => {
+                    warn!(
+                        "Received status {} from {upstream} in attempt {attempt} of {attempts}",
+                        response.status()
+                    );
+                    match retry_after(&response) {
+                        Some(delay) if delay > max_delay => {
+                            warn!(
+                                "{upstream} asked to retry after {}s, which exceeds the maximum delay",
+                                delay.as_secs()
+                            );
+                            return Ok(response);
+                        }
+                        Some(delay) => delay,
+                        None => self.backoff(attempt),
+                    }
+                }
+                Ok(response) => return Ok(response),
+                Err(e) if attempt < attempts && (e.is_connect() || e.is_timeout()) => {
+                    warn!("Request to {upstream} failed in attempt {attempt} of {attempts}: {e}");
+                    self.backoff(attempt)
+                }
+                Err(e) => return Err(transport_error(e)),
+            };
+
+            self.upstream_retries_total
+                .with_label_values(&[upstream])
+                .inc();
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Sends the request, checks the status of the response and decodes its body as JSON.
+    pub async fn send_json<T: DeserializeOwned>(
+        &self,
+        upstream: &str,
+        request: RequestBuilder,
+    ) -> Result<T, ExporterError> {
+        let body = self.send_text(upstream, request).await?;
+
+        decode_json(upstream, &body)
+    }
+
+    /// Sends the request, checks the status of the response and returns its body.
+    pub async fn send_text(
+        &self,
+        upstream: &str,
+        request: RequestBuilder,
+    ) -> Result<String, ExporterError> {
+        let response = self.send(upstream, request).await?;
+
+        read_text(upstream, response).await
+    }
+
+    /// Sends an info request with the given weight against the info rate limit, checks
+    /// the status of the response and returns its body.
+    pub async fn send_info_text(
+        &self,
+        request: RequestBuilder,
+        weight: u32,
+    ) -> Result<String, ExporterError> {
+        let response = self.send_weighted("info", request, Some(weight)).await?;
+
+        read_text("info", response).await
+    }
+
+    fn is_retryable(&self, status: StatusCode) -> bool {
+        self.retry_config
+            .retryable_statuses
+            .contains(&status.as_u16())
+    }
+
+    /// Exponential backoff with jitter between half and the full delay of the attempt.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay_ms = self
+            .retry_config
+            .base_delay_ms
+            .saturating_mul(2_u64.saturating_pow(attempt - 1))
+            .min(self.retry_config.max_delay_ms);
+        let jittered_delay_ms = rand::rng().random_range(delay_ms / 2..=delay_ms);
+
+        Duration::from_millis(jittered_delay_ms)
+    }
+}
+
+async fn read_text(upstream: &str, response: Response) -> Result<String, ExporterError> {
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|source| ExporterError::Transport {
+            upstream: upstream.to_string(),
+            source: source.without_url(),
+        })?;
+
+    if !status.is_success() {
+        return Err(ExporterError::http_status(upstream, status, &body));
+    }
+
+    Ok(body)
+}
+
+pub fn decode_json<T: DeserializeOwned>(upstream: &str, body: &str) -> Result<T, ExporterError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(body);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| ExporterError::JsonDecode {
+        upstream: upstream.to_string(),
+        path: e.path().to_string(),
+        source: e.into_inner(),
+    })
+}
+
+/// The reqwest client builder with the timeouts, user agent, pool and proxy of the config.
+pub(crate) fn client_builder(http_config: &HttpConfig) -> anyhow::Result<ClientBuilder> {
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(http_config.connect_timeout_secs))
+        .timeout(Duration::from_secs(http_config.request_timeout_secs))
+        .user_agent(http_config.user_agent.clone())
+        .pool_idle_timeout(Duration::from_secs(http_config.pool_idle_timeout_secs))
+        .pool_max_idle_per_host(http_config.pool_max_idle_per_host);
+
+    if let Some(proxy) = &http_config.proxy {
+        builder = builder.proxy(
+            Proxy::all(proxy).context(format!("Failed to configure the HTTP proxy {proxy}"))?,
+        );
+    }
+
+    Ok(builder)
+}
+
+/// The delay of the `Retry-After` header, which is either a number of seconds or an
+/// HTTP date. A date in the past means no delay.
+fn retry_after(response: &Response) -> Option<Duration> {
+    if ![
+        StatusCode::TOO_MANY_REQUESTS,
+        StatusCode::SERVICE_UNAVAILABLE,
+    ]
+    .contains(&response.status())
+    {
+        return None;
+    }
+
+    let retry_after = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(retry_after_secs) = retry_after.parse() {
+        return Some(Duration::from_secs(retry_after_secs));
+    }
+    let retry_at = DateTime::parse_from_rfc2822(retry_after).ok()?;
+
+    Some(
+        (retry_at.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
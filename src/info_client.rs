@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::{
+    consts::MAINNET_INFO_API_URL,
+    error::ExporterError,
+    http_client::{HttpClient, client_builder, decode_json},
+    protocol_meta::{PerpMetaData, SpotMetaData, ValidatorSummary},
+    user_details::{
+        Delegation, DelegatorReward, FrontendOpenOrder, SubAccount, UserStakingSummary,
+    },
+    utils::{CacheConfig, HttpConfig, InfoRequest, RateLimitConfig, RetryConfig},
+    vault_details::{PortfolioEntry, VaultDetails},
+};
+
+/// Typed client of the Hyperliquid info API.
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use hypurr_exporter::info_client::InfoClient;
+///
+/// let info_client = InfoClient::builder()
+///     .base_url("https://api.hyperliquid-testnet.xyz/info")
+///     .build()?;
+/// let spot_meta = info_client.spot_meta().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct InfoClient {
+    http_client: HttpClient,
+    base_url: String,
+}
+
+#[derive(Debug, Default)]
+pub struct InfoClientBuilder {
+    base_url: Option<String>,
+    http_client: Option<HttpClient>,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+}
+
+impl InfoClientBuilder {
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Uses an existing HTTP client, e.g. to share it with other clients. The timeouts
+    /// of the builder are ignored in this case.
+    pub fn http_client(mut self, http_client: HttpClient) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<InfoClient> {
+        let http_client = match self.http_client {
+            Some(http_client) => http_client,
+            None => {
+                let mut builder = client_builder(&HttpConfig::default())?;
+                for (name, timeout) in [
+                    ("connect", self.connect_timeout),
+                    ("request", self.request_timeout),
+                ] {
+                    if timeout.is_some_and(|timeout| timeout.is_zero()) {
+                        bail!("The {name} timeout must not be zero");
+                    }
+                }
+                if let Some(connect_timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(connect_timeout);
+                }
+                if let Some(request_timeout) = self.request_timeout {
+                    builder = builder.timeout(request_timeout);
+                }
+                let client = builder.build().context("Failed to build the HTTP client")?;
+
+                HttpClient::from_client(
+                    client,
+                    &RetryConfig::default(),
+                    &RateLimitConfig::default(),
+                    &CacheConfig::default(),
+                )?
+            }
+        };
+
+        Ok(InfoClient {
+            http_client,
+            base_url: self
+                .base_url
+                .unwrap_or_else(|| MAINNET_INFO_API_URL.to_string()),
+        })
+    }
+}
+
+impl InfoClient {
+    pub fn builder() -> InfoClientBuilder {
+        InfoClientBuilder::default()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn http_client(&self) -> &HttpClient {
+        &self.http_client
+    }
+
+    pub async fn vault_details(&self, vault_address: &str) -> Result<VaultDetails, ExporterError> {
+        self.request(InfoRequest::VaultDetails {
+            vault_address: vault_address.to_string(),
+        })
+        .await
+    }
+
+    pub async fn portfolio(&self, user: &str) -> Result<Vec<PortfolioEntry>, ExporterError> {
+        self.request(InfoRequest::Portfolio {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    pub async fn delegator_summary(&self, user: &str) -> Result<UserStakingSummary, ExporterError> {
+        self.request(InfoRequest::DelegatorSummary {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    pub async fn delegations(&self, user: &str) -> Result<Vec<Delegation>, ExporterError> {
+        self.request(InfoRequest::Delegations {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    pub async fn delegator_rewards(
+        &self,
+        user: &str,
+    ) -> Result<Vec<DelegatorReward>, ExporterError> {
+        self.request(InfoRequest::DelegatorRewards {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    pub async fn frontend_open_orders(
+        &self,
+        user: &str,
+    ) -> Result<Vec<FrontendOpenOrder>, ExporterError> {
+        self.request(InfoRequest::FrontendOpenOrders {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    /// The sub-accounts of the master account, empty if it has none.
+    pub async fn sub_accounts(&self, user: &str) -> Result<Vec<SubAccount>, ExporterError> {
+        let sub_accounts: Option<Vec<SubAccount>> = self
+            .request(InfoRequest::SubAccounts {
+                user: user.to_string(),
+            })
+            .await?;
+
+        Ok(sub_accounts.unwrap_or_default())
+    }
+
+    pub async fn spot_meta(&self) -> Result<SpotMetaData, ExporterError> {
+        self.request(InfoRequest::SpotMeta).await
+    }
+
+    pub async fn meta(&self) -> Result<PerpMetaData, ExporterError> {
+        self.request(InfoRequest::Meta).await
+    }
+
+    pub async fn validator_summaries(&self) -> Result<Vec<ValidatorSummary>, ExporterError> {
+        self.request(InfoRequest::ValidatorSummaries).await
+    }
+
+    /// Sends an info request and decodes its response. Responses are cached according to
+    /// the configured TTLs, identical in-flight requests are coalesced and upstream calls
+    /// are rate limited by the weight of the request.
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        info_request: InfoRequest,
+    ) -> Result<T, ExporterError> {
+        let http_client = &self.http_client;
+        let request_type = info_request.request_type();
+        let ttl = http_client.info_cache().ttl(request_type);
+        let cache_key = format!("{} {}", self.base_url, info_request.cache_key());
+
+        let cached_body = match ttl {
+            Some(_) => http_client.info_cache().get(request_type, &cache_key).await,
+            None => None,
+        };
+        if let Some(body) = cached_body {
+            debug!("Serving info request {request_type} from the cache");
+            return decode_json("info", &body);
+        }
+
+        let body = http_client
+            .info_single_flight()
+            .run(&cache_key, || async {
+                let request = http_client.post(&self.base_url).json(&info_request);
+                debug!("Sending info request {request_type}");
+                http_client
+                    .send_info_text(request, info_request.weight())
+                    .await
+            })
+            .await?;
+        let response = decode_json("info", &body)?;
+
+        if let Some(ttl) = ttl {
+            http_client.info_cache().insert(cache_key, body, ttl).await;
+        }
+
+        Ok(response)
+    }
+}
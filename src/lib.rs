@@ -1,8 +1,22 @@
 pub mod consts;
 pub use consts::MAINNET_INFO_API_URL;
+pub mod cache;
+pub mod cli;
+pub mod error;
+pub mod exposition;
 pub mod financial_meta;
+pub mod health;
+pub mod http_client;
+pub mod info_client;
 pub mod metrics;
+pub mod privacy;
+pub mod probe;
 pub mod protocol_meta;
+pub mod rate_limiter;
+pub mod single_flight;
+pub mod snapshot;
+pub mod transport;
 pub mod user_details;
 pub mod utils;
 pub mod vault_details;
+pub mod web;
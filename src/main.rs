@@ -1,151 +1,664 @@
 use axum::{
-    Router,
+    Json, Router,
     body::Body,
-    extract::State,
-    http::{StatusCode, header},
-    response::Response,
-    routing::get,
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
 };
-use chrono::Utc;
+use clap::Parser;
 use hypurr_exporter::{
+    cli::{Cli, Command},
+    exposition::{CreatedTimestamps, ExpositionFormat},
     financial_meta::get_coingecko_data,
+    health::{Health, HealthReport},
+    http_client::HttpClient,
+    info_client::InfoClient,
     metrics::Metrics,
-    protocol_meta::get_protocol_data,
-    user_details::get_user_details,
-    utils::{Config, read_config},
+    probe::{ProbeModule, probe},
+    protocol_meta::{ValidatorSet, get_evm_data, get_protocol_data, get_validator_data},
+    snapshot::{CollectorSnapshot, SnapshotView, Snapshots},
+    user_details::{AccountDetails, UserDetails, get_account_details},
+    utils::{Config, Network, is_valid_address},
     vault_details::get_vault_details,
+    web::{Authenticator, load_tls_config},
 };
-use prometheus::{Encoder, Registry, TextEncoder};
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::Mutex;
-use tracing::{error, info};
+use prometheus::{Registry, proto::MetricFamily};
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+#[cfg(unix)]
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::{Mutex, RwLock, Semaphore};
+use tracing::{error, info, warn};
 
 #[derive(Clone)]
 pub struct AppState {
+    cli: Arc<Cli>,
     metrics: Arc<Mutex<Metrics>>,
     registry: Registry,
+    process_registry: Registry,
+    created_timestamps: Arc<CreatedTimestamps>,
+    http_client: HttpClient,
+    health: Arc<Health>,
+    snapshots: Arc<std::sync::Mutex<Snapshots>>,
+    probe_semaphore: Arc<Semaphore>,
+    authenticator: Arc<Authenticator>,
+    reloadable: Arc<RwLock<ReloadableState>>,
+}
+
+/// The part of the state which is replaced on a reload of the config.
+#[derive(Clone)]
+pub struct ReloadableState {
     config: Config,
+    info_client: InfoClient,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
 
-    let mut labels = HashMap::new();
-    labels.insert("timestamp".to_string(), Utc::now().to_string());
+    let cli = Cli::parse();
+    let config = cli.read_config().await?;
+    if let Some(Command::CheckConfig) = cli.command {
+        println!("{}", toml::to_string(&config.redacted())?);
+        return Ok(());
+    }
+
+    let address_mask = config.address_mask();
+    info!(
+        "Read config with user address: {} and vault address: {} on {}",
+        config
+            .user_address
+            .as_deref()
+            .map_or("None".into(), |address| address_mask.display(address)),
+        config
+            .vault_address
+            .as_deref()
+            .map_or("None".into(), |address| address_mask.display(address)),
+        config.network.as_str()
+    );
 
-    let registry = Registry::new_custom(Some("hyperliquid".to_string()), Some(labels))?;
+    let registry = Registry::new_custom(
+        Some("hyperliquid".to_string()),
+        Some(registry_labels(config.network)),
+    )?;
     let metrics = Metrics::new()?;
     metrics.register(&registry)?;
+    // The build info and process metrics keep their standard names without the prefix.
+    let process_registry = Registry::new_custom(None, Some(registry_labels(config.network)))?;
+    Metrics::register_process_metrics(&process_registry)?;
     let metrics = Arc::new(Mutex::new(metrics));
 
-    let config = read_config().await?;
-    info!(
-        "Read config.toml with user address: {} and vault address: {}",
-        config.clone().user_address.unwrap_or("None".into()),
-        config.clone().vault_address.unwrap_or("None".into())
-    );
+    let http_client = HttpClient::new(
+        &config.http,
+        &config.retry,
+        &config.rate_limit,
+        &config.cache,
+    )?;
+    http_client.register(&registry)?;
+    let info_client = InfoClient::builder()
+        .base_url(config.info_api_url())
+        .http_client(http_client.clone())
+        .build()?;
+
+    metrics.lock().await.record_config_reload(true);
 
+    let addr = config.listen_address.clone();
+    let metrics_path = config.metrics_path.clone();
+    let tls_config = match &config.web.tls_server_config {
+        Some(tls_server_config) => Some(load_tls_config(tls_server_config).await?),
+        None => None,
+    };
     let state = AppState {
+        cli: Arc::new(cli),
         metrics,
         registry,
-        config,
+        process_registry,
+        created_timestamps: Arc::new(CreatedTimestamps::new()),
+        http_client,
+        health: Arc::new(Health::new(&config)),
+        snapshots: Arc::new(std::sync::Mutex::new(Snapshots::default())),
+        probe_semaphore: Arc::new(Semaphore::new(config.max_concurrent_probes)),
+        authenticator: Arc::new(Authenticator::new()),
+        reloadable: Arc::new(RwLock::new(ReloadableState {
+            config,
+            info_client,
+        })),
     };
 
+    #[cfg(unix)]
+    spawn_sighup_handler(state.clone())?;
+
     let app = Router::new()
-        .route("/metrics", get(handle_metrics))
+        .route(&metrics_path, get(handle_metrics))
+        .route("/-/reload", post(handle_reload))
+        .route("/healthz", get(handle_healthz))
+        .route("/readyz", get(handle_readyz))
+        .route("/probe", get(handle_probe))
+        .route("/api/v1/snapshot", get(handle_snapshot))
+        .route("/api/v1/users/{address}", get(handle_user))
+        .layer(middleware::from_fn_with_state(state.clone(), require_auth))
         .with_state(state);
 
-    let addr = String::from("0.0.0.0:3000");
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    match tls_config {
+        Some(tls_config) => {
+            let listener = std::net::TcpListener::bind(&addr)?;
+            listener.set_nonblocking(true)?;
 
-    info!("Listening on {}.", addr);
+            info!("Listening on {} with TLS.", addr);
 
-    axum::serve(listener, app.into_make_service()).await?;
+            axum_server::from_tcp_rustls(listener, tls_config)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        None => {
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+
+            info!("Listening on {}.", addr);
+
+            axum::serve(listener, app.into_make_service()).await?;
+        }
+    }
 
     Ok(())
 }
 
+/// Re-reads the config with the flags of the command line and swaps it in. An invalid
+/// config is rejected and the previous one is kept.
+async fn reload_config(app_state: &AppState) -> anyhow::Result<()> {
+    let result = swap_config(app_state).await;
+    app_state
+        .metrics
+        .lock()
+        .await
+        .record_config_reload(result.is_ok());
+
+    result
+}
+
+async fn swap_config(app_state: &AppState) -> anyhow::Result<()> {
+    let mut config = app_state.cli.read_config().await?;
+
+    let mut reloadable = app_state.reloadable.write().await;
+    let previous_config = &reloadable.config;
+    if config.listen_address != previous_config.listen_address
+        || config.metrics_path != previous_config.metrics_path
+        || config.network != previous_config.network
+        || config.max_concurrent_probes != previous_config.max_concurrent_probes
+        || config.http != previous_config.http
+        || config.retry != previous_config.retry
+        || config.rate_limit != previous_config.rate_limit
+        || config.cache != previous_config.cache
+        || config.web.tls_server_config != previous_config.web.tls_server_config
+    {
+        warn!(
+            "Changes of listen_address, metrics_path, network, max_concurrent_probes, [http], [retry], [rate_limit], [cache] and [web.tls_server_config] only take effect after a restart"
+        );
+    }
+    // The network is part of the const labels, so the upstreams of the running network
+    // are kept until a restart instead of switching them under the old label.
+    config.network = previous_config.network;
+    let info_client = InfoClient::builder()
+        .base_url(config.info_api_url())
+        .http_client(app_state.http_client.clone())
+        .build()?;
+
+    app_state.health.configure(&config);
+    *reloadable = ReloadableState {
+        config,
+        info_client,
+    };
+    info!("Reloaded the config");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn spawn_sighup_handler(app_state: AppState) -> anyhow::Result<()> {
+    let mut hangup = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            info!("Received SIGHUP, reloading the config");
+            if let Err(e) = reload_config(&app_state).await {
+                error!("Failed to reload the config: {e:#}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+pub async fn handle_reload(
+    State(app_state): State<AppState>,
+) -> Result<&'static str, (StatusCode, String)> {
+    reload_config(&app_state).await.map_err(|e| {
+        let error_message = format!("Failed to reload the config: {e:#}");
+        error!(error_message);
+        (StatusCode::INTERNAL_SERVER_ERROR, error_message)
+    })?;
+
+    Ok("Reloaded the config\n")
+}
+
+/// Liveness of the exporter, which is given as long as it serves requests.
+pub async fn handle_healthz(State(app_state): State<AppState>) -> Json<HealthReport> {
+    Json(app_state.health.report("ok"))
+}
+
+/// Readiness of the exporter, which is given once every enabled collector refreshed
+/// successfully at least once.
+pub async fn handle_readyz(State(app_state): State<AppState>) -> (StatusCode, Json<HealthReport>) {
+    if app_state.health.is_ready() {
+        (StatusCode::OK, Json(app_state.health.report("ready")))
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(app_state.health.report("not_ready")),
+        )
+    }
+}
+
 pub async fn handle_metrics(
     State(app_state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<Response, (StatusCode, String)> {
     let AppState {
         metrics,
         registry,
-        config,
+        process_registry,
+        created_timestamps,
+        http_client,
+        health,
+        snapshots,
+        reloadable,
+        ..
     } = app_state;
+    let ReloadableState {
+        config,
+        info_client,
+    } = reloadable.read().await.clone();
+    let address_mask = config.address_mask();
 
-    let coingecko_financial_meta = if let Some(coingecko_key) = config.coingecko_key {
-        info!("Querying financial meta information from Coingecko");
-        get_coingecko_data(&coingecko_key)
+    // The collectors run concurrently, so that requests they have in common are coalesced.
+    let coingecko_financial_meta = async {
+        if let Some(coingecko_key) = &config.coingecko_key {
+            info!("Querying financial meta information from Coingecko");
+            match get_coingecko_data(
+                &http_client,
+                &config.coingecko_api_url(),
+                coingecko_key.expose(),
+            )
             .await
-            .unwrap_or_else(|e| {
-                error!("Failed receive the financial meta details: {e:?}");
-                (0.0, 0, 0, 0, 0.0, 0.0)
-            })
-    } else {
-        info!(
-            "No Coingecko key got configured. Skipping the query of financial meta information from Coingecko!"
-        );
-        (0.0, 0, 0, 0, 0.0, 0.0)
+            {
+                Ok(coingecko_financial_meta) => {
+                    health.record_success("coingecko");
+                    snapshots
+                        .lock()
+                        .unwrap()
+                        .coingecko
+                        .record_success(coingecko_financial_meta.into());
+                    Some(coingecko_financial_meta)
+                }
+                Err(e) => {
+                    error!("Failed receive the financial meta details: {e}");
+                    metrics.lock().await.record_collector_error("coingecko", &e);
+                    health.record_error("coingecko", &e);
+                    snapshots.lock().unwrap().coingecko.record_error(&e);
+                    Some((0.0, 0, 0, 0, 0.0, 0.0))
+                }
+            }
+        } else {
+            info!(
+                "No Coingecko key got configured. Skipping the query of financial meta information from Coingecko!"
+            );
+            snapshots.lock().unwrap().coingecko.clear();
+            None
+        }
     };
 
-    let protocol_meta = if let Some(alchemy_key) = config.alchemy_key {
-        info!("Querying protocol meta information from Alchemy and Hyperliquid");
-        get_protocol_data(&alchemy_key)
-            .await
-            .unwrap_or_else(|e| {
-                error!("Failed receive the protocol meta details: {e:?}");
-                (0, 0, 0.0, 0, 0)
-            })
-    } else {
-        info!(
-            "No Alchemy key got configured. Skipping the query of protocol meta information from Alchemy and Hyperliquid!"
-        );
-        (0, 0, 0.0, 0, 0)
+    let evm_meta = async {
+        if let Some(evm_rpc_url) = config.evm_rpc_url() {
+            info!("Querying HyperEVM information from the EVM RPC");
+            match get_evm_data(&http_client, &evm_rpc_url, &config.evm_rpc_headers).await {
+                Ok(evm_meta) => {
+                    health.record_success("evm");
+                    snapshots
+                        .lock()
+                        .unwrap()
+                        .evm
+                        .record_success(evm_meta.into());
+                    Some(evm_meta)
+                }
+                Err(e) => {
+                    error!("Failed receive the HyperEVM details: {e}");
+                    metrics.lock().await.record_collector_error("evm", &e);
+                    health.record_error("evm", &e);
+                    snapshots.lock().unwrap().evm.record_error(&e);
+                    Some((0, 0))
+                }
+            }
+        } else {
+            info!(
+                "No Alchemy key or EVM RPC URL got configured. Skipping the query of HyperEVM information!"
+            );
+            snapshots.lock().unwrap().evm.clear();
+            None
+        }
     };
 
-    let vault_details = if let Some(vault_address) = config.vault_address {
-        info!("Querying vault details for address: {}", vault_address);
-        get_vault_details(&vault_address).await.unwrap_or_else(|e| {
-            error!("Failed receive the vault details: {e:?}");
-            (0.0, 0.0, 0.0, 0.0, 0.0, 0, 0.0, 0.0, false, false)
-        })
-    } else {
-        info!("No vault address got configured. Skipping the query of vault details!");
-        (0.0, 0.0, 0.0, 0.0, 0.0, 0, 0.0, 0.0, false, false)
+    let protocol_meta = async {
+        info!("Querying protocol meta information from Hyperliquid");
+        match get_protocol_data(&info_client).await {
+            Ok(protocol_meta) => {
+                health.record_success("protocol");
+                snapshots
+                    .lock()
+                    .unwrap()
+                    .protocol
+                    .record_success(protocol_meta.into());
+                protocol_meta
+            }
+            Err(e) => {
+                error!("Failed receive the protocol meta details: {e}");
+                metrics.lock().await.record_collector_error("protocol", &e);
+                health.record_error("protocol", &e);
+                snapshots.lock().unwrap().protocol.record_error(&e);
+                (0.0, 0, 0)
+            }
+        }
     };
 
-    let user_details = if let Some(user_address) = config.user_address {
-        info!("Querying user details for address: {}", user_address);
-        get_user_details(user_address).await.unwrap_or_else(|e| {
-            error!("Failed receive the user details: {e:?}");
-            (0.0, 0.0, 0.0, 0.0, 0.0, 0, 0.0)
-        })
-    } else {
-        info!("No user address got configured. Skipping the query of user details!");
-        (0.0, 0.0, 0.0, 0.0, 0.0, 0, 0.0)
+    let validator_set = async {
+        info!("Querying validator set information from Hyperliquid");
+        match get_validator_data(&info_client).await {
+            Ok(validator_set) => {
+                health.record_success("validator");
+                snapshots
+                    .lock()
+                    .unwrap()
+                    .validator
+                    .record_success(validator_set.clone());
+                validator_set
+            }
+            Err(e) => {
+                error!("Failed receive the validator set details: {e}");
+                metrics.lock().await.record_collector_error("validator", &e);
+                health.record_error("validator", &e);
+                snapshots.lock().unwrap().validator.record_error(&e);
+                ValidatorSet::default()
+            }
+        }
+    };
+
+    let vault_details = async {
+        if let Some(vault_address) = &config.vault_address {
+            info!(
+                "Querying vault details for address: {}",
+                address_mask.display(vault_address)
+            );
+            match get_vault_details(&info_client, vault_address).await {
+                Ok(vault_details) => {
+                    health.record_success("vault");
+                    snapshots
+                        .lock()
+                        .unwrap()
+                        .vault
+                        .record_success(vault_details.into());
+                    Some(vault_details)
+                }
+                Err(e) => {
+                    error!("Failed receive the vault details: {e}");
+                    metrics.lock().await.record_collector_error("vault", &e);
+                    health.record_error("vault", &e);
+                    snapshots.lock().unwrap().vault.record_error(&e);
+                    Some((0.0, 0.0, 0.0, 0.0, 0.0, 0, 0.0, 0.0, false, false))
+                }
+            }
+        } else {
+            info!("No vault address got configured. Skipping the query of vault details!");
+            snapshots.lock().unwrap().vault.clear();
+            None
+        }
+    };
+
+    let account_details = async {
+        if let Some(user_address) = &config.user_address {
+            info!(
+                "Querying user details for address: {}",
+                address_mask.display(user_address)
+            );
+            match get_account_details(&info_client, user_address.clone()).await {
+                // The addresses are masked before they end up in the labels. The snapshot
+                // keeps them unmasked for the lookup by address and masks them when served.
+                Ok(mut account_details) => {
+                    snapshots
+                        .lock()
+                        .unwrap()
+                        .user
+                        .record_success(account_details.accounts.clone());
+                    // Failed sub-accounts are errors of the collector, even though the
+                    // other accounts are exported.
+                    if account_details.is_complete() {
+                        health.record_success("user");
+                    }
+                    for e in account_details.errors.iter() {
+                        metrics.lock().await.record_collector_error("user", e);
+                        health.record_error("user", e);
+                        snapshots.lock().unwrap().user.record_error(e);
+                    }
+                    for user_details in account_details.accounts.iter_mut() {
+                        user_details.address = address_mask.display(&user_details.address);
+                        user_details.master = address_mask.display(&user_details.master);
+                    }
+                    account_details
+                }
+                Err(e) => {
+                    error!("Failed receive the user details: {e}");
+                    metrics.lock().await.record_collector_error("user", &e);
+                    health.record_error("user", &e);
+                    snapshots.lock().unwrap().user.record_error(&e);
+                    AccountDetails::default()
+                }
+            }
+        } else {
+            info!("No user address got configured. Skipping the query of user details!");
+            snapshots.lock().unwrap().user.clear();
+            AccountDetails::default()
+        }
     };
 
+    let (
+        coingecko_financial_meta,
+        evm_meta,
+        protocol_meta,
+        validator_set,
+        vault_details,
+        account_details,
+    ) = tokio::join!(
+        coingecko_financial_meta,
+        evm_meta,
+        protocol_meta,
+        validator_set,
+        vault_details,
+        account_details
+    );
+
     let metrics = metrics.lock().await;
     metrics
-        .update(coingecko_financial_meta, protocol_meta, vault_details, user_details)
+        .update(
+            coingecko_financial_meta,
+            evm_meta,
+            protocol_meta,
+            validator_set,
+            vault_details,
+            account_details,
+        )
         .map_err(|e| {
             let error_message = format!("Failed to update metrics: {e:?}");
             error!(error_message);
             (StatusCode::INTERNAL_SERVER_ERROR, error_message)
         })?;
 
-    let encoder = TextEncoder::new();
-    let metric_families = registry.gather();
-    let encoded_metrics = encoder.encode_to_string(&metric_families).map_err(|e| {
-        let error_message = format!("Failed to encode metrics: {e:?}");
+    let mut metric_families = registry.gather();
+    metric_families.extend(process_registry.gather());
+
+    encode_metrics(metric_families, &headers, &created_timestamps)
+}
+
+/// Runs the user or vault collector against an address which isn't configured, e.g.
+/// `/probe?target=0x...&module=vault`.
+pub async fn handle_probe(
+    State(app_state): State<AppState>,
+    Query(params): Query<ProbeParams>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    if !is_valid_address(&params.target) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "The target {:?} is not an address of 0x and 40 hex characters",
+                params.target
+            ),
+        ));
+    }
+
+    // Probes are rejected instead of queued, so that they can't pile up on the quotas of
+    // the upstream APIs.
+    let Ok(_permit) = app_state.probe_semaphore.try_acquire() else {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many concurrent probes".to_string(),
+        ));
+    };
+
+    let ReloadableState {
+        config,
+        info_client,
+    } = app_state.reloadable.read().await.clone();
+    let target = config.address_mask().display(&params.target);
+    info!("Probing the {} module of {target}", params.module.as_str());
+    // The series of a probe are new, so they count since the start of the probe.
+    let created_timestamps = CreatedTimestamps::new();
+    let metric_families = probe(
+        &info_client,
+        params.module,
+        &params.target,
+        &config.address_mask(),
+        registry_labels(config.network),
+    )
+    .await
+    .map_err(|e| {
+        let error_message = format!("Failed to probe {target}: {e:?}");
         error!(error_message);
         (StatusCode::INTERNAL_SERVER_ERROR, error_message)
     })?;
 
+    encode_metrics(metric_families, &headers, &created_timestamps)
+}
+
+/// Rejects requests without valid credentials if `[web]` configures users or tokens. The
+/// credentials are taken from the current config, so they can be changed by a reload.
+async fn require_auth(State(app_state): State<AppState>, request: Request, next: Next) -> Response {
+    let web_config = app_state.reloadable.read().await.config.web.clone();
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|authorization| authorization.to_str().ok());
+
+    if app_state
+        .authenticator
+        .is_authorized(&web_config, authorization)
+        .await
+    {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(
+                header::WWW_AUTHENTICATE,
+                Authenticator::challenge(&web_config),
+            )],
+            "Unauthorized",
+        )
+            .into_response()
+    }
+}
+
+/// Returns the latest data of every collector as JSON, as collected by the last scrapes.
+pub async fn handle_snapshot(State(app_state): State<AppState>) -> Json<SnapshotView> {
+    let config = app_state.reloadable.read().await.config.clone();
+
+    Json(app_state.snapshots.lock().unwrap().view(
+        Duration::from_secs(config.snapshot_max_age_secs),
+        &config.address_mask(),
+    ))
+}
+
+/// Returns the latest data of the configured user or one of its sub-accounts as JSON.
+pub async fn handle_user(
+    State(app_state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<CollectorSnapshot<UserDetails>>, (StatusCode, String)> {
+    let config = app_state.reloadable.read().await.config.clone();
+
+    app_state
+        .snapshots
+        .lock()
+        .unwrap()
+        .user(
+            &address,
+            Duration::from_secs(config.snapshot_max_age_secs),
+            &config.address_mask(),
+        )
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("No snapshot of the user {address:?}"),
+            )
+        })
+}
+
+#[derive(Deserialize)]
+pub struct ProbeParams {
+    target: String,
+    module: ProbeModule,
+}
+
+/// The const labels of the registries with the `hyperliquid` prefix.
+fn registry_labels(network: Network) -> HashMap<String, String> {
+    let mut labels = HashMap::new();
+    if network != Network::Mainnet {
+        labels.insert("network".to_string(), network.as_str().to_string());
+    }
+
+    labels
+}
+
+fn encode_metrics(
+    mut metric_families: Vec<MetricFamily>,
+    headers: &HeaderMap,
+    created_timestamps: &CreatedTimestamps,
+) -> Result<Response, (StatusCode, String)> {
+    metric_families.sort_by(|a, b| a.name().cmp(b.name()));
+
+    let format = ExpositionFormat::negotiate(
+        headers
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok()),
+    );
+    let encoded_metrics = format
+        .encode(&metric_families, created_timestamps)
+        .map_err(|e| {
+            let error_message = format!("Failed to encode metrics: {e:?}");
+            error!(error_message);
+            (StatusCode::INTERNAL_SERVER_ERROR, error_message)
+        })?;
+
     let response = Response::builder()
-        .header(header::CONTENT_TYPE, encoder.format_type())
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(header::VARY, "Accept")
         .body(Body::from(encoded_metrics))
         .map_err(|e| {
             let error_message = format!("Failed to build response: {e:?}");
@@ -1,76 +1,139 @@
-use prometheus::{Error, Gauge, Opts, Registry};
+use chrono::Utc;
+#[cfg(target_os = "linux")]
+use prometheus::process_collector::ProcessCollector;
+use prometheus::{Error, Gauge, GaugeVec, IntCounterVec, Opts, Registry};
+
+use crate::{
+    error::ExporterError,
+    protocol_meta::ValidatorSet,
+    user_details::{AccountDetails, UserDetails},
+    vault_details::VaultMetrics,
+};
+
+/// The labels of the metrics of optional collectors. They are vectors without labels, so
+/// that their series can be removed when the collector gets disabled.
+const NO_LABELS: &[&str] = &[];
 
 #[derive(Debug)]
 pub struct Metrics {
-    pub hyperliquid_price: Gauge,
-    pub hyperliquid_marketcap: Gauge,
-    pub hyperliquid_fdv: Gauge,
-    pub hyperliquid_tvl: Gauge,
-    pub hyperliquid_circulating_supply: Gauge,
-    pub hyperliquid_total_supply: Gauge,
-
-    pub hyperliquid_block_number: Gauge,
-    pub hyperliquid_base_fee: Gauge,
+    pub hyperliquid_price: GaugeVec,
+    pub hyperliquid_marketcap: GaugeVec,
+    pub hyperliquid_fdv: GaugeVec,
+    pub hyperliquid_tvl: GaugeVec,
+    pub hyperliquid_circulating_supply: GaugeVec,
+    pub hyperliquid_total_supply: GaugeVec,
+
+    pub hyperliquid_block_number: GaugeVec,
+    pub hyperliquid_base_fee: GaugeVec,
     pub hyperliquid_af_account_value: Gauge,
     pub hyperliquid_num_spot_tokens: Gauge,
     pub hyperliquid_num_perp_tokens: Gauge,
 
-    pub vault_value: Gauge,
-    pub vault_pnl: Gauge,
-    pub vault_apr: Gauge,
-    pub vault_leader_fraction: Gauge,
-    pub vault_leader_comission: Gauge,
-    pub vault_num_followers: Gauge,
-    pub vault_max_distributable: Gauge,
-    pub vault_max_withdrawable: Gauge,
-    pub vault_is_closed: Gauge,
-    pub vault_allow_deposits: Gauge,
-
-    pub user_account_value: Gauge,
-    pub user_pnl: Gauge,
-    pub user_staking_delegated: Gauge,
-    pub user_staking_undelegated: Gauge,
-    pub user_staking_pending_withdrawal: Gauge,
-    pub user_num_open_orders: Gauge,
-    pub user_value_open_orders: Gauge,
+    pub validator_num_active: Gauge,
+    pub validator_stake: GaugeVec,
+    pub validator_is_jailed: GaugeVec,
+    pub validator_is_active: GaugeVec,
+    pub validator_commission: GaugeVec,
+    pub validator_num_recent_blocks: GaugeVec,
+    pub validator_uptime_fraction: GaugeVec,
+
+    pub vault_value: GaugeVec,
+    pub vault_pnl: GaugeVec,
+    pub vault_apr: GaugeVec,
+    pub vault_leader_fraction: GaugeVec,
+    pub vault_leader_comission: GaugeVec,
+    pub vault_num_followers: GaugeVec,
+    pub vault_max_distributable: GaugeVec,
+    pub vault_max_withdrawable: GaugeVec,
+    pub vault_is_closed: GaugeVec,
+    pub vault_allow_deposits: GaugeVec,
+
+    pub user_account_value: GaugeVec,
+    pub user_pnl: GaugeVec,
+    pub user_staking_delegated: GaugeVec,
+    pub user_staking_undelegated: GaugeVec,
+    pub user_staking_pending_withdrawal: GaugeVec,
+    pub user_staking_num_pending_withdrawals: GaugeVec,
+    pub user_staking_rewards: GaugeVec,
+    pub user_staking_delegation_amount: GaugeVec,
+    pub user_staking_delegation_locked_until_timestamp_seconds: GaugeVec,
+    pub user_num_open_orders: GaugeVec,
+    pub user_value_open_orders: GaugeVec,
+    pub user_open_orders_exposure: GaugeVec,
+    pub user_open_orders_unparseable: GaugeVec,
+    pub user_open_orders: GaugeVec,
+    pub user_open_orders_notional: GaugeVec,
+    pub user_oldest_open_order_age_seconds: GaugeVec,
+    pub user_num_sub_accounts: GaugeVec,
+    pub user_total_account_value: GaugeVec,
+    pub user_total_pnl: GaugeVec,
+
+    pub collector_errors_total: IntCounterVec,
+
+    pub config_last_reload_successful: Gauge,
+    pub config_last_reload_success_timestamp_seconds: Gauge,
 }
 
 impl Metrics {
     pub fn new() -> Result<Self, Error> {
         let metrics = Metrics {
-            hyperliquid_price: Gauge::with_opts(Opts::new(
-                "hyperliquid_price",
-                "The current market price of the Hyperliquid token (HYPE) in USD",
-            ))?,
-            hyperliquid_marketcap: Gauge::with_opts(Opts::new(
-                "hyperliquid_marketcap",
-                "The total market value of Hyperliquid's circulating supply",
-            ))?,
-            hyperliquid_fdv: Gauge::with_opts(Opts::new(
-                "hyperliquid_fdv",
-                "The theoretical market capitalization of a coin if the entirety of its supply is in circulation, based on its current market price",
-            ))?,
-            hyperliquid_tvl: Gauge::with_opts(Opts::new(
-                "hyperliquid_tvl",
-                "Capital deposited into the platform in the form of loan collateral or liquidity trading pool",
-            ))?,
-            hyperliquid_circulating_supply: Gauge::with_opts(Opts::new(
-                "hyperliquid_circulating_supply",
-                "The amount of coins that are circulating in the market and are tradeable by the public",
-            ))?,
-            hyperliquid_total_supply: Gauge::with_opts(Opts::new(
-                "hyperliquid_total_supply",
-                "The amount of coins that have already been created, minus any coins that have been burned",
-            ))?,
+            hyperliquid_price: GaugeVec::new(
+                Opts::new(
+                    "hyperliquid_price",
+                    "The current market price of the Hyperliquid token (HYPE) in USD",
+                ),
+                NO_LABELS,
+            )?,
+            hyperliquid_marketcap: GaugeVec::new(
+                Opts::new(
+                    "hyperliquid_marketcap",
+                    "The total market value of Hyperliquid's circulating supply",
+                ),
+                NO_LABELS,
+            )?,
+            hyperliquid_fdv: GaugeVec::new(
+                Opts::new(
+                    "hyperliquid_fdv",
+                    "The theoretical market capitalization of a coin if the entirety of its supply is in circulation, based on its current market price",
+                ),
+                NO_LABELS,
+            )?,
+            hyperliquid_tvl: GaugeVec::new(
+                Opts::new(
+                    "hyperliquid_tvl",
+                    "Capital deposited into the platform in the form of loan collateral or liquidity trading pool",
+                ),
+                NO_LABELS,
+            )?,
+            hyperliquid_circulating_supply: GaugeVec::new(
+                Opts::new(
+                    "hyperliquid_circulating_supply",
+                    "The amount of coins that are circulating in the market and are tradeable by the public",
+                ),
+                NO_LABELS,
+            )?,
+            hyperliquid_total_supply: GaugeVec::new(
+                Opts::new(
+                    "hyperliquid_total_supply",
+                    "The amount of coins that have already been created, minus any coins that have been burned",
+                ),
+                NO_LABELS,
+            )?,
 
-            hyperliquid_block_number: Gauge::with_opts(Opts::new(
-                "hyperliquid_block_number",
-                "The current block number of the HyperEVM",
-            ))?,
-            hyperliquid_base_fee: Gauge::with_opts(Opts::new(
-                "hyperliquid_base_fee",
-                "The current base fee for the next small block on HyperEVM",
-            ))?,
+            hyperliquid_block_number: GaugeVec::new(
+                Opts::new(
+                    "hyperliquid_block_number",
+                    "The current block number of the HyperEVM",
+                ),
+                NO_LABELS,
+            )?,
+            hyperliquid_base_fee: GaugeVec::new(
+                Opts::new(
+                    "hyperliquid_base_fee",
+                    "The current base fee for the next small block on HyperEVM",
+                ),
+                NO_LABELS,
+            )?,
             hyperliquid_af_account_value: Gauge::with_opts(Opts::new(
                 "hyperliquid_af_account_value",
                 "The current account value of the Hyperliquid Assistance Fund",
@@ -84,69 +147,275 @@ impl Metrics {
                 "TThe current number of perp tokens on Hyperliquid",
             ))?,
 
-
-            vault_value: Gauge::with_opts(Opts::new(
-                "vault_value",
-                "The total value locked (TVL) of the vault",
-            ))?,
-            vault_pnl: Gauge::with_opts(Opts::new("vault_pnl", "The profitability of the vault"))?,
-            vault_apr: Gauge::with_opts(Opts::new(
-                "vault_apr",
-                "The annual percentage rate (APR) for the vault",
-            ))?,
-            vault_leader_fraction: Gauge::with_opts(Opts::new(
-                "vault_leader_fraction",
-                "The fraction of the vault controlled or owned by the leader",
-            ))?,
-            vault_leader_comission: Gauge::with_opts(Opts::new(
-                "vault_leader_comission",
-                "The commission that the leader earns",
-            ))?,
-            vault_num_followers: Gauge::with_opts(Opts::new(
-                "vault_num_followers",
-                "The number of followers of the vault",
-            ))?,
-            vault_max_distributable: Gauge::with_opts(Opts::new(
-                "vault_max_distributable",
-                "The maximum amount that can be distributed from the vault",
-            ))?,
-            vault_max_withdrawable: Gauge::with_opts(Opts::new(
-                "vault_max_withdrawable",
-                "The maximum amount that can be withdrawn from the vault",
-            ))?,
-            vault_is_closed: Gauge::with_opts(Opts::new(
-                "vault_is_closed",
-                "A flag indicating whether the vault is closed or not",
-            ))?,
-            vault_allow_deposits: Gauge::with_opts(Opts::new(
-                "vault_allow_deposits",
-                "A flag indicating whether new deposits are allowed into the vault",
+            validator_num_active: Gauge::with_opts(Opts::new(
+                "validator_num_active",
+                "The number of validators in the active set of HyperCore",
             ))?,
+            validator_stake: GaugeVec::new(
+                Opts::new(
+                    "validator_stake",
+                    "The amount of HYPE staked to the validator",
+                ),
+                &["validator", "name"],
+            )?,
+            validator_is_jailed: GaugeVec::new(
+                Opts::new(
+                    "validator_is_jailed",
+                    "A flag indicating whether the validator is jailed or not",
+                ),
+                &["validator", "name"],
+            )?,
+            validator_is_active: GaugeVec::new(
+                Opts::new(
+                    "validator_is_active",
+                    "A flag indicating whether the validator is in the active set or not",
+                ),
+                &["validator", "name"],
+            )?,
+            validator_commission: GaugeVec::new(
+                Opts::new(
+                    "validator_commission",
+                    "The commission that the validator charges on staking rewards",
+                ),
+                &["validator", "name"],
+            )?,
+            validator_num_recent_blocks: GaugeVec::new(
+                Opts::new(
+                    "validator_num_recent_blocks",
+                    "The number of blocks recently proposed by the validator",
+                ),
+                &["validator", "name"],
+            )?,
+            validator_uptime_fraction: GaugeVec::new(
+                Opts::new(
+                    "validator_uptime_fraction",
+                    "The fraction of time the validator was up during the period",
+                ),
+                &["validator", "name", "period"],
+            )?,
 
-            user_account_value: Gauge::with_opts(Opts::new(
-                "user_account_value",
-                "The value of the user wallet",
-            ))?,
-            user_pnl: Gauge::with_opts(Opts::new("user_pnl", "The profitability of the user"))?,
-            user_staking_delegated: Gauge::with_opts(Opts::new(
-                "user_staking_delegated",
-                "The value of funds delegated to stakers",
-            ))?,
-            user_staking_undelegated: Gauge::with_opts(Opts::new(
-                "user_staking_undelegated",
-                "The value of funds undelegated from stakers",
-            ))?,
-            user_staking_pending_withdrawal: Gauge::with_opts(Opts::new(
-                "user_staking_pending_withdrawal",
-                "The value of funds which are waiting be unstaked",
-            ))?,
-            user_num_open_orders: Gauge::with_opts(Opts::new(
-                "user_num_open_orders",
-                "The number of open orders by a user",
+            vault_value: GaugeVec::new(
+                Opts::new("vault_value", "The total value locked (TVL) of the vault"),
+                NO_LABELS,
+            )?,
+            vault_pnl: GaugeVec::new(
+                Opts::new("vault_pnl", "The profitability of the vault"),
+                NO_LABELS,
+            )?,
+            vault_apr: GaugeVec::new(
+                Opts::new(
+                    "vault_apr",
+                    "The annual percentage rate (APR) for the vault",
+                ),
+                NO_LABELS,
+            )?,
+            vault_leader_fraction: GaugeVec::new(
+                Opts::new(
+                    "vault_leader_fraction",
+                    "The fraction of the vault controlled or owned by the leader",
+                ),
+                NO_LABELS,
+            )?,
+            vault_leader_comission: GaugeVec::new(
+                Opts::new(
+                    "vault_leader_comission",
+                    "The commission that the leader earns",
+                ),
+                NO_LABELS,
+            )?,
+            vault_num_followers: GaugeVec::new(
+                Opts::new(
+                    "vault_num_followers",
+                    "The number of followers of the vault",
+                ),
+                NO_LABELS,
+            )?,
+            vault_max_distributable: GaugeVec::new(
+                Opts::new(
+                    "vault_max_distributable",
+                    "The maximum amount that can be distributed from the vault",
+                ),
+                NO_LABELS,
+            )?,
+            vault_max_withdrawable: GaugeVec::new(
+                Opts::new(
+                    "vault_max_withdrawable",
+                    "The maximum amount that can be withdrawn from the vault",
+                ),
+                NO_LABELS,
+            )?,
+            vault_is_closed: GaugeVec::new(
+                Opts::new(
+                    "vault_is_closed",
+                    "A flag indicating whether the vault is closed or not",
+                ),
+                NO_LABELS,
+            )?,
+            vault_allow_deposits: GaugeVec::new(
+                Opts::new(
+                    "vault_allow_deposits",
+                    "A flag indicating whether new deposits are allowed into the vault",
+                ),
+                NO_LABELS,
+            )?,
+
+            user_account_value: GaugeVec::new(
+                Opts::new("user_account_value", "The value of the user wallet"),
+                &["user", "master"],
+            )?,
+            user_pnl: GaugeVec::new(
+                Opts::new("user_pnl", "The profitability of the user"),
+                &["user", "master"],
+            )?,
+            user_staking_delegated: GaugeVec::new(
+                Opts::new(
+                    "user_staking_delegated",
+                    "The value of funds delegated to stakers",
+                ),
+                &["user", "master"],
+            )?,
+            user_staking_undelegated: GaugeVec::new(
+                Opts::new(
+                    "user_staking_undelegated",
+                    "The value of funds undelegated from stakers",
+                ),
+                &["user", "master"],
+            )?,
+            user_staking_pending_withdrawal: GaugeVec::new(
+                Opts::new(
+                    "user_staking_pending_withdrawal",
+                    "The value of funds which are waiting be unstaked",
+                ),
+                &["user", "master"],
+            )?,
+            user_staking_num_pending_withdrawals: GaugeVec::new(
+                Opts::new(
+                    "user_staking_num_pending_withdrawals",
+                    "The number of staking withdrawals which are still pending",
+                ),
+                &["user", "master"],
+            )?,
+            user_staking_rewards: GaugeVec::new(
+                Opts::new(
+                    "user_staking_rewards",
+                    "The accumulated staking rewards of the user by reward source",
+                ),
+                &["user", "master", "source"],
+            )?,
+            user_staking_delegation_amount: GaugeVec::new(
+                Opts::new(
+                    "user_staking_delegation_amount",
+                    "The amount delegated by the user to a validator",
+                ),
+                &["user", "master", "validator"],
+            )?,
+            user_staking_delegation_locked_until_timestamp_seconds: GaugeVec::new(
+                Opts::new(
+                    "user_staking_delegation_locked_until_timestamp_seconds",
+                    "The Unix timestamp in seconds until the delegation to a validator is locked",
+                ),
+                &["user", "master", "validator"],
+            )?,
+            user_num_open_orders: GaugeVec::new(
+                Opts::new(
+                    "user_num_open_orders",
+                    "The number of open orders by a user",
+                ),
+                &["user", "master"],
+            )?,
+            user_value_open_orders: GaugeVec::new(
+                Opts::new(
+                    "user_value_open_orders",
+                    "The value of open orders by a user",
+                ),
+                &["user", "master"],
+            )?,
+            user_open_orders_exposure: GaugeVec::new(
+                Opts::new(
+                    "user_open_orders_exposure",
+                    "The notional value of open orders by a user per market and side",
+                ),
+                &["user", "master", "market", "side"],
+            )?,
+            user_open_orders_unparseable: GaugeVec::new(
+                Opts::new(
+                    "user_open_orders_unparseable",
+                    "The number of open orders by a user whose price or size could not be parsed",
+                ),
+                &["user", "master"],
+            )?,
+            user_open_orders: GaugeVec::new(
+                Opts::new(
+                    "user_open_orders",
+                    "The number of open orders by a user per coin, side and order type",
+                ),
+                &[
+                    "user",
+                    "master",
+                    "coin",
+                    "side",
+                    "order_type",
+                    "reduce_only",
+                ],
+            )?,
+            user_open_orders_notional: GaugeVec::new(
+                Opts::new(
+                    "user_open_orders_notional",
+                    "The notional value of open orders by a user per coin, side and order type",
+                ),
+                &[
+                    "user",
+                    "master",
+                    "coin",
+                    "side",
+                    "order_type",
+                    "reduce_only",
+                ],
+            )?,
+            user_oldest_open_order_age_seconds: GaugeVec::new(
+                Opts::new(
+                    "user_oldest_open_order_age_seconds",
+                    "The age in seconds of the oldest open order by a user",
+                ),
+                &["user", "master"],
+            )?,
+            user_num_sub_accounts: GaugeVec::new(
+                Opts::new(
+                    "user_num_sub_accounts",
+                    "The number of sub-accounts of the master account",
+                ),
+                &["master"],
+            )?,
+            user_total_account_value: GaugeVec::new(
+                Opts::new(
+                    "user_total_account_value",
+                    "The aggregated value of the master account and all of its sub-accounts",
+                ),
+                &["master"],
+            )?,
+            user_total_pnl: GaugeVec::new(
+                Opts::new(
+                    "user_total_pnl",
+                    "The aggregated profitability of the master account and all of its sub-accounts",
+                ),
+                &["master"],
+            )?,
+
+            collector_errors_total: IntCounterVec::new(
+                Opts::new(
+                    "collector_errors_total",
+                    "The number of failed collector runs by collector and error kind",
+                ),
+                &["collector", "kind"],
+            )?,
+
+            config_last_reload_successful: Gauge::with_opts(Opts::new(
+                "config_last_reload_successful",
+                "Whether the last reload of the config succeeded",
             ))?,
-            user_value_open_orders: Gauge::with_opts(Opts::new(
-                "user_value_open_orders",
-                "The value of open orders by a user",
+            config_last_reload_success_timestamp_seconds: Gauge::with_opts(Opts::new(
+                "config_last_reload_success_timestamp_seconds",
+                "The Unix timestamp of the last successful reload of the config",
             ))?,
         };
 
@@ -167,6 +436,29 @@ impl Metrics {
         registry.register(Box::new(self.hyperliquid_num_spot_tokens.clone()))?;
         registry.register(Box::new(self.hyperliquid_num_perp_tokens.clone()))?;
 
+        registry.register(Box::new(self.validator_num_active.clone()))?;
+        registry.register(Box::new(self.validator_stake.clone()))?;
+        registry.register(Box::new(self.validator_is_jailed.clone()))?;
+        registry.register(Box::new(self.validator_is_active.clone()))?;
+        registry.register(Box::new(self.validator_commission.clone()))?;
+        registry.register(Box::new(self.validator_num_recent_blocks.clone()))?;
+        registry.register(Box::new(self.validator_uptime_fraction.clone()))?;
+
+        self.register_vault(registry)?;
+
+        self.register_users(registry)?;
+
+        registry.register(Box::new(self.collector_errors_total.clone()))?;
+
+        registry.register(Box::new(self.config_last_reload_successful.clone()))?;
+        registry.register(Box::new(
+            self.config_last_reload_success_timestamp_seconds.clone(),
+        ))?;
+
+        Ok(())
+    }
+
+    pub fn register_vault(&self, registry: &Registry) -> Result<(), Error> {
         registry.register(Box::new(self.vault_value.clone()))?;
         registry.register(Box::new(self.vault_pnl.clone()))?;
         registry.register(Box::new(self.vault_apr.clone()))?;
@@ -178,61 +470,343 @@ impl Metrics {
         registry.register(Box::new(self.vault_is_closed.clone()))?;
         registry.register(Box::new(self.vault_allow_deposits.clone()))?;
 
+        Ok(())
+    }
+
+    pub fn register_users(&self, registry: &Registry) -> Result<(), Error> {
         registry.register(Box::new(self.user_account_value.clone()))?;
         registry.register(Box::new(self.user_pnl.clone()))?;
         registry.register(Box::new(self.user_staking_delegated.clone()))?;
         registry.register(Box::new(self.user_staking_undelegated.clone()))?;
         registry.register(Box::new(self.user_staking_pending_withdrawal.clone()))?;
+        registry.register(Box::new(self.user_staking_num_pending_withdrawals.clone()))?;
+        registry.register(Box::new(self.user_staking_rewards.clone()))?;
+        registry.register(Box::new(self.user_staking_delegation_amount.clone()))?;
+        registry.register(Box::new(
+            self.user_staking_delegation_locked_until_timestamp_seconds
+                .clone(),
+        ))?;
         registry.register(Box::new(self.user_num_open_orders.clone()))?;
         registry.register(Box::new(self.user_value_open_orders.clone()))?;
+        registry.register(Box::new(self.user_open_orders_exposure.clone()))?;
+        registry.register(Box::new(self.user_open_orders_unparseable.clone()))?;
+        registry.register(Box::new(self.user_open_orders.clone()))?;
+        registry.register(Box::new(self.user_open_orders_notional.clone()))?;
+        registry.register(Box::new(self.user_oldest_open_order_age_seconds.clone()))?;
+        registry.register(Box::new(self.user_num_sub_accounts.clone()))?;
+        registry.register(Box::new(self.user_total_account_value.clone()))?;
+        registry.register(Box::new(self.user_total_pnl.clone()))?;
+
+        Ok(())
+    }
+
+    pub fn record_collector_error(&self, collector: &str, error: &ExporterError) {
+        self.collector_errors_total
+            .with_label_values(&[collector, error.kind()])
+            .inc();
+    }
+
+    /// Records the outcome of a config reload, the initial load counts as a reload.
+    pub fn record_config_reload(&self, success: bool) {
+        self.config_last_reload_successful
+            .set(if success { 1.0 } else { 0.0 });
+        if success {
+            self.config_last_reload_success_timestamp_seconds
+                .set(Utc::now().timestamp() as f64);
+        }
+    }
+
+    /// Registers the metrics of the exporter process itself. They are kept out of the
+    /// `hyperliquid` namespace and therefore go into a registry without a prefix.
+    pub fn register_process_metrics(registry: &Registry) -> Result<(), Error> {
+        let build_info = Gauge::with_opts(
+            Opts::new(
+                "hypurr_exporter_build_info",
+                "A metric with a constant '1' value labeled by the version and the git commit of the exporter",
+            )
+            .const_label("version", env!("CARGO_PKG_VERSION"))
+            .const_label("git_sha", env!("GIT_SHA")),
+        )?;
+        build_info.set(1.0);
+        registry.register(Box::new(build_info))?;
+
+        #[cfg(target_os = "linux")]
+        registry.register(Box::new(ProcessCollector::for_self()))?;
+
+        // The process collector, which includes the start time, is only available on Linux.
+        #[cfg(not(target_os = "linux"))]
+        {
+            let process_start_time_seconds = Gauge::with_opts(Opts::new(
+                "process_start_time_seconds",
+                "Start time of the process since unix epoch in seconds.",
+            ))?;
+            process_start_time_seconds.set(Utc::now().timestamp() as f64);
+            registry.register(Box::new(process_start_time_seconds))?;
+        }
 
         Ok(())
     }
 
     pub fn update(
         &self,
-        coingecko_financial_meta: (f64, i64, i64, i64, f64, f64),
-        protocol_meta: (u64, u64, f64, usize, usize),
-        vault_details: (f64, f64, f64, f64, f64, usize, f64, f64, bool, bool),
-        user_details: (f64, f64, f64, f64, f64, usize, f64),
+        coingecko_financial_meta: Option<(f64, i64, i64, i64, f64, f64)>,
+        evm_meta: Option<(u64, u64)>,
+        protocol_meta: (f64, usize, usize),
+        validator_set: ValidatorSet,
+        vault_details: Option<VaultMetrics>,
+        account_details: AccountDetails,
     ) -> Result<(), Error> {
-        self.hyperliquid_price.set(coingecko_financial_meta.0);
-        self.hyperliquid_marketcap
-            .set(coingecko_financial_meta.1 as f64);
-        self.hyperliquid_fdv.set(coingecko_financial_meta.2 as f64);
-        self.hyperliquid_tvl.set(coingecko_financial_meta.3 as f64);
-        self.hyperliquid_circulating_supply
-            .set(coingecko_financial_meta.4);
-        self.hyperliquid_total_supply
-            .set(coingecko_financial_meta.5);
-
-        self.hyperliquid_block_number.set(protocol_meta.0 as f64);
-        self.hyperliquid_base_fee.set(protocol_meta.1 as f64);
-        self.hyperliquid_af_account_value.set(protocol_meta.2);
-        self.hyperliquid_num_spot_tokens.set(protocol_meta.3 as f64);
-        self.hyperliquid_num_perp_tokens.set(protocol_meta.4 as f64);
-
-        self.vault_value.set(vault_details.0);
-        self.vault_pnl.set(vault_details.1);
-        self.vault_apr.set(vault_details.2);
-        self.vault_leader_fraction.set(vault_details.3);
-        self.vault_leader_comission.set(vault_details.4);
-        self.vault_num_followers.set(vault_details.5 as f64);
-        self.vault_max_distributable.set(vault_details.6);
-        self.vault_max_withdrawable.set(vault_details.7);
-        self.vault_is_closed.set(bool_to_f64(vault_details.8));
-        self.vault_allow_deposits.set(bool_to_f64(vault_details.9));
-
-        self.user_account_value.set(user_details.0);
-        self.user_pnl.set(user_details.1);
-        self.user_staking_delegated.set(user_details.2);
-        self.user_staking_undelegated.set(user_details.3);
-        self.user_staking_pending_withdrawal.set(user_details.4);
-        self.user_num_open_orders.set(user_details.5 as f64);
-        self.user_value_open_orders.set(user_details.6);
+        self.update_coingecko(coingecko_financial_meta);
+        self.update_evm(evm_meta);
+
+        self.hyperliquid_af_account_value.set(protocol_meta.0);
+        self.hyperliquid_num_spot_tokens.set(protocol_meta.1 as f64);
+        self.hyperliquid_num_perp_tokens.set(protocol_meta.2 as f64);
+
+        self.validator_num_active
+            .set(validator_set.num_active as f64);
+        self.validator_stake.reset();
+        self.validator_is_jailed.reset();
+        self.validator_is_active.reset();
+        self.validator_commission.reset();
+        self.validator_num_recent_blocks.reset();
+        self.validator_uptime_fraction.reset();
+        for validator in validator_set.validators.iter() {
+            let labels = [validator.address.as_str(), validator.name.as_str()];
+            self.validator_stake
+                .with_label_values(&labels)
+                .set(validator.stake);
+            self.validator_is_jailed
+                .with_label_values(&labels)
+                .set(bool_to_f64(validator.is_jailed));
+            self.validator_is_active
+                .with_label_values(&labels)
+                .set(bool_to_f64(validator.is_active));
+            self.validator_commission
+                .with_label_values(&labels)
+                .set(validator.commission);
+            self.validator_num_recent_blocks
+                .with_label_values(&labels)
+                .set(validator.num_recent_blocks as f64);
+            for (period, uptime_fraction) in validator.uptime_fraction.iter() {
+                self.validator_uptime_fraction
+                    .with_label_values(&[labels[0], labels[1], period])
+                    .set(*uptime_fraction);
+            }
+        }
+
+        match vault_details {
+            Some(vault_details) => self.update_vault(vault_details),
+            None => self.reset_vault(),
+        }
+
+        self.update_users(&account_details.accounts, account_details.is_complete());
 
         Ok(())
     }
+
+    /// Sets the CoinGecko metrics, or removes their series if the collector is disabled.
+    fn update_coingecko(&self, coingecko_financial_meta: Option<(f64, i64, i64, i64, f64, f64)>) {
+        let gauges = [
+            &self.hyperliquid_price,
+            &self.hyperliquid_marketcap,
+            &self.hyperliquid_fdv,
+            &self.hyperliquid_tvl,
+            &self.hyperliquid_circulating_supply,
+            &self.hyperliquid_total_supply,
+        ];
+        let Some(coingecko_financial_meta) = coingecko_financial_meta else {
+            gauges.iter().for_each(|gauge| gauge.reset());
+            return;
+        };
+
+        let values = [
+            coingecko_financial_meta.0,
+            coingecko_financial_meta.1 as f64,
+            coingecko_financial_meta.2 as f64,
+            coingecko_financial_meta.3 as f64,
+            coingecko_financial_meta.4,
+            coingecko_financial_meta.5,
+        ];
+        for (gauge, value) in gauges.iter().zip(values) {
+            gauge.with_label_values(NO_LABELS).set(value);
+        }
+    }
+
+    /// Sets the HyperEVM metrics, or removes their series if the collector is disabled.
+    fn update_evm(&self, evm_meta: Option<(u64, u64)>) {
+        let gauges = [&self.hyperliquid_block_number, &self.hyperliquid_base_fee];
+        let Some(evm_meta) = evm_meta else {
+            gauges.iter().for_each(|gauge| gauge.reset());
+            return;
+        };
+
+        let values = [evm_meta.0 as f64, evm_meta.1 as f64];
+        for (gauge, value) in gauges.iter().zip(values) {
+            gauge.with_label_values(NO_LABELS).set(value);
+        }
+    }
+
+    pub fn update_vault(&self, vault_details: VaultMetrics) {
+        let values = [
+            vault_details.0,
+            vault_details.1,
+            vault_details.2,
+            vault_details.3,
+            vault_details.4,
+            vault_details.5 as f64,
+            vault_details.6,
+            vault_details.7,
+            bool_to_f64(vault_details.8),
+            bool_to_f64(vault_details.9),
+        ];
+        for (gauge, value) in self.vault_gauges().iter().zip(values) {
+            gauge.with_label_values(NO_LABELS).set(value);
+        }
+    }
+
+    /// Removes the series of the vault, e.g. after its address got removed by a reload.
+    pub fn reset_vault(&self) {
+        self.vault_gauges().iter().for_each(|gauge| gauge.reset());
+    }
+
+    fn vault_gauges(&self) -> [&GaugeVec; 10] {
+        [
+            &self.vault_value,
+            &self.vault_pnl,
+            &self.vault_apr,
+            &self.vault_leader_fraction,
+            &self.vault_leader_comission,
+            &self.vault_num_followers,
+            &self.vault_max_distributable,
+            &self.vault_max_withdrawable,
+            &self.vault_is_closed,
+            &self.vault_allow_deposits,
+        ]
+    }
+
+    /// Replaces the series of all users with the metrics of the accounts, so that removed
+    /// users disappear. The totals of a master account are only set if `complete`, as they
+    /// would undercount without the failed sub-accounts.
+    pub fn update_users(&self, user_details: &[UserDetails], complete: bool) {
+        self.user_account_value.reset();
+        self.user_pnl.reset();
+        self.user_staking_delegated.reset();
+        self.user_staking_undelegated.reset();
+        self.user_staking_pending_withdrawal.reset();
+        self.user_staking_num_pending_withdrawals.reset();
+        self.user_staking_rewards.reset();
+        self.user_staking_delegation_amount.reset();
+        self.user_staking_delegation_locked_until_timestamp_seconds
+            .reset();
+        self.user_num_open_orders.reset();
+        self.user_value_open_orders.reset();
+        self.user_open_orders_exposure.reset();
+        self.user_open_orders_unparseable.reset();
+        self.user_open_orders.reset();
+        self.user_open_orders_notional.reset();
+        self.user_oldest_open_order_age_seconds.reset();
+        self.user_num_sub_accounts.reset();
+        self.user_total_account_value.reset();
+        self.user_total_pnl.reset();
+
+        for user_details in user_details.iter() {
+            let user = user_details.address.as_str();
+            let master = user_details.master.as_str();
+            let labels = [user, master];
+
+            self.user_account_value
+                .with_label_values(&labels)
+                .set(user_details.account_value);
+            self.user_pnl
+                .with_label_values(&labels)
+                .set(user_details.pnl);
+            self.user_staking_delegated
+                .with_label_values(&labels)
+                .set(user_details.staking_delegated);
+            self.user_staking_undelegated
+                .with_label_values(&labels)
+                .set(user_details.staking_undelegated);
+            self.user_staking_pending_withdrawal
+                .with_label_values(&labels)
+                .set(user_details.staking_pending_withdrawal);
+            self.user_staking_num_pending_withdrawals
+                .with_label_values(&labels)
+                .set(user_details.staking_num_pending_withdrawals as f64);
+            self.user_num_open_orders
+                .with_label_values(&labels)
+                .set(user_details.num_open_orders as f64);
+            self.user_value_open_orders
+                .with_label_values(&labels)
+                .set(user_details.value_open_orders);
+            self.user_oldest_open_order_age_seconds
+                .with_label_values(&labels)
+                .set(user_details.oldest_open_order_age);
+
+            self.user_open_orders_unparseable
+                .with_label_values(&labels)
+                .set(user_details.num_unparseable_open_orders as f64);
+
+            for exposure in user_details.open_order_exposures.iter() {
+                self.user_open_orders_exposure
+                    .with_label_values(&[
+                        user,
+                        master,
+                        exposure.market.as_str(),
+                        exposure.side.as_str(),
+                    ])
+                    .set(exposure.notional);
+            }
+
+            for group in user_details.open_order_groups.iter() {
+                let reduce_only = group.reduce_only.to_string();
+                let labels = [
+                    user,
+                    master,
+                    group.coin.as_str(),
+                    group.side.as_str(),
+                    group.order_type.as_str(),
+                    reduce_only.as_str(),
+                ];
+                self.user_open_orders
+                    .with_label_values(&labels)
+                    .set(group.count as f64);
+                self.user_open_orders_notional
+                    .with_label_values(&labels)
+                    .set(group.notional);
+            }
+
+            for reward in user_details.staking_rewards.iter() {
+                self.user_staking_rewards
+                    .with_label_values(&[user, master, reward.source.as_str()])
+                    .set(reward.amount);
+            }
+
+            for delegation in user_details.staking_delegations.iter() {
+                let labels = [user, master, delegation.validator.as_str()];
+                self.user_staking_delegation_amount
+                    .with_label_values(&labels)
+                    .set(delegation.amount);
+                self.user_staking_delegation_locked_until_timestamp_seconds
+                    .with_label_values(&labels)
+                    .set(delegation.locked_until as f64 / 1000.0);
+            }
+
+            if !complete {
+                continue;
+            }
+            let num_sub_accounts = self.user_num_sub_accounts.with_label_values(&[master]);
+            if user != master {
+                num_sub_accounts.inc();
+            }
+            self.user_total_account_value
+                .with_label_values(&[master])
+                .add(user_details.account_value);
+            self.user_total_pnl
+                .with_label_values(&[master])
+                .add(user_details.pnl);
+        }
+    }
 }
 
 fn bool_to_f64(v: bool) -> f64 {
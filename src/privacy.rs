@@ -0,0 +1,81 @@
+use std::{collections::HashMap, convert::Infallible, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Placeholder which is shown instead of a secret.
+pub const REDACTED: &str = "<redacted>";
+
+/// A secret like an API key, which is never shown by `Debug`, `Display` or `Serialize`.
+/// The value itself is only accessible via `expose`.
+#[derive(Clone, Default, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl FromStr for Secret {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s))
+    }
+}
+
+/// Shows addresses in logs and labels either by their configured alias, shortened to
+/// their prefix and suffix (e.g. `0x8c96...7f1d`) if masking is enabled, or in full.
+#[derive(Clone, Debug, Default)]
+pub struct AddressMask {
+    mask_addresses: bool,
+    aliases: HashMap<String, String>,
+}
+
+impl AddressMask {
+    pub fn new(mask_addresses: bool, aliases: &HashMap<String, String>) -> Self {
+        Self {
+            mask_addresses,
+            aliases: aliases
+                .iter()
+                .map(|(address, alias)| (address.to_lowercase(), alias.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn display(&self, address: &str) -> String {
+        if let Some(alias) = self.aliases.get(&address.to_lowercase()) {
+            return alias.clone();
+        }
+
+        match (self.mask_addresses, address.get(..6), address.get(38..)) {
+            (true, Some(prefix), Some(suffix)) if address.len() == 42 => {
+                format!("{prefix}...{suffix}")
+            }
+            _ => address.to_string(),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{collections::HashMap, time::Instant};
+
+use prometheus::{Gauge, Opts, Registry, proto::MetricFamily};
+use serde::Deserialize;
+use tracing::error;
+
+use crate::{
+    info_client::InfoClient, metrics::Metrics, privacy::AddressMask,
+    user_details::get_user_details, vault_details::get_vault_details,
+};
+
+/// The collector which is run against the target of a probe.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeModule {
+    User,
+    Vault,
+}
+
+impl ProbeModule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProbeModule::User => "user",
+            ProbeModule::Vault => "vault",
+        }
+    }
+}
+
+/// Collects the metrics of the module for an address which isn't configured, like the
+/// blackbox exporter does for its targets. The metrics go into fresh registries, so
+/// probes neither share series with each other nor with the configured addresses.
+///
+/// A failed collection isn't an error of the probe, it's reported by `probe_success`.
+pub async fn probe(
+    info_client: &InfoClient,
+    module: ProbeModule,
+    target: &str,
+    address_mask: &AddressMask,
+    labels: HashMap<String, String>,
+) -> Result<Vec<MetricFamily>, prometheus::Error> {
+    let registry = Registry::new_custom(Some("hyperliquid".to_string()), Some(labels.clone()))?;
+    let metrics = Metrics::new()?;
+
+    let start = Instant::now();
+    let success = match module {
+        ProbeModule::User => {
+            metrics.register_users(&registry)?;
+            match get_user_details(info_client, target.to_string()).await {
+                Ok(mut user_details) => {
+                    user_details.address = address_mask.display(&user_details.address);
+                    user_details.master = address_mask.display(&user_details.master);
+                    // The totals of the master account would only count the target.
+                    metrics.update_users(&[user_details], false);
+                    true
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to probe the user details of {}: {e}",
+                        address_mask.display(target)
+                    );
+                    false
+                }
+            }
+        }
+        ProbeModule::Vault => {
+            metrics.register_vault(&registry)?;
+            match get_vault_details(info_client, target).await {
+                Ok(vault_details) => {
+                    metrics.update_vault(vault_details);
+                    true
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to probe the vault details of {}: {e}",
+                        address_mask.display(target)
+                    );
+                    false
+                }
+            }
+        }
+    };
+
+    // Like the metrics of the blackbox exporter, these carry no prefix.
+    let probe_registry = Registry::new_custom(None, Some(labels))?;
+    let probe_success = Gauge::with_opts(Opts::new(
+        "probe_success",
+        "Whether the probe of the target succeeded",
+    ))?;
+    probe_success.set(if success { 1.0 } else { 0.0 });
+    probe_registry.register(Box::new(probe_success))?;
+    let probe_duration_seconds = Gauge::with_opts(Opts::new(
+        "probe_duration_seconds",
+        "The duration of the probe of the target in seconds",
+    ))?;
+    probe_duration_seconds.set(start.elapsed().as_secs_f64());
+    probe_registry.register(Box::new(probe_duration_seconds))?;
+
+    let mut metric_families = if success {
+        registry.gather()
+    } else {
+        Vec::new()
+    };
+    metric_families.extend(probe_registry.gather());
+
+    Ok(metric_families)
+}
@@ -1,13 +1,11 @@
-use anyhow::bail;
-use reqwest::Client;
+use reqwest::header::HeaderValue;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
+use std::collections::HashMap;
 use tracing::error;
 
 use crate::{
-    consts::ALCHEMY_API_URL,
-    utils::{InfoRequest, send_info_request},
-    vault_details::PortfolioEntry,
+    error::ExporterError, http_client::HttpClient, info_client::InfoClient, privacy::Secret,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -73,34 +71,94 @@ pub struct MarginTier {
     pub max_leverage: u32,
 }
 
-pub async fn query_alchemy_api(http_client: Client, url: &str, body: Value) -> anyhow::Result<u64> {
-    let response = http_client
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorSummary {
+    pub validator: String,
+    pub signer: String,
+    pub name: String,
+    pub description: String,
+    pub n_recent_blocks: u64,
+    pub stake: u64,
+    pub is_jailed: bool,
+    pub unjailable_after: Option<u64>,
+    pub is_active: bool,
+    pub commission: String,
+    pub stats: Vec<(String, ValidatorStats)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorStats {
+    pub uptime_fraction: String,
+    pub predicted_apr: String,
+    pub n_samples: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ValidatorSet {
+    pub num_active: usize,
+    pub validators: Vec<Validator>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Validator {
+    pub address: String,
+    pub name: String,
+    pub stake: f64,
+    pub is_jailed: bool,
+    pub is_active: bool,
+    pub commission: f64,
+    pub num_recent_blocks: u64,
+    pub uptime_fraction: Vec<(String, f64)>,
+}
+
+/// Number of decimals of the raw HYPE stake reported by `validatorSummaries`.
+const STAKE_DECIMALS: i32 = 8;
+
+pub async fn query_evm_rpc(
+    http_client: &HttpClient,
+    url: &str,
+    headers: &HashMap<String, Secret>,
+    body: Value,
+) -> Result<u64, ExporterError> {
+    let mut request = http_client
         .post(url)
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
+        .header("Content-Type", "application/json");
+    for (name, value) in headers.iter() {
+        request = match HeaderValue::from_str(value.expose()) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                request.header(name, value)
+            }
+            // Invalid values fail when the request is built.
+            Err(_) => request.header(name, value.expose()),
+        };
+    }
+
+    let json_response: Value = http_client
+        .send_json("evm_rpc", request.json(&body))
         .await?;
-    let json_response: Value = response.json().await?;
 
     let hex_result = if let Some(hex_result) = json_response["result"].as_str() {
         hex_result
     } else {
-        bail!("Failed to extract hex result from the json response!");
+        return Err(ExporterError::MissingField(
+            "Failed to extract hex result from the json response!".to_string(),
+        ));
     };
     let clean_hex = hex_result.trim_start_matches("0x");
-    let decimal_result = match u64::from_str_radix(clean_hex, 16) {
-        Ok(decimal_result) => decimal_result,
-        Err(e) => {
-            bail!("Invalid hex string for block number: {e}")
-        }
-    };
+    let decimal_result = u64::from_str_radix(clean_hex, 16)?;
 
     Ok(decimal_result)
 }
 
-pub async fn get_protocol_data(alchemy_key: &str) -> anyhow::Result<(u64, u64, f64, usize, usize)> {
-    let http_client = Client::new();
-    let url = format!("{}{}", ALCHEMY_API_URL, alchemy_key);
+pub async fn get_evm_data(
+    http_client: &HttpClient,
+    evm_rpc_url: &str,
+    evm_rpc_headers: &HashMap<String, Secret>,
+) -> Result<(u64, u64), ExporterError> {
+    let url = evm_rpc_url.to_string();
 
     let body = json!({
         "jsonrpc": "2.0",
@@ -108,7 +166,7 @@ pub async fn get_protocol_data(alchemy_key: &str) -> anyhow::Result<(u64, u64, f
         "params": [],
         "id": 1
     });
-    let block_number = query_alchemy_api(http_client.clone(), &url, body).await?;
+    let block_number = query_evm_rpc(http_client, &url, evm_rpc_headers, body).await?;
 
     let body = json!({
         "jsonrpc": "2.0",
@@ -116,19 +174,27 @@ pub async fn get_protocol_data(alchemy_key: &str) -> anyhow::Result<(u64, u64, f
         "params": [],
         "id": 1
     });
-    let base_fee = query_alchemy_api(http_client, &url, body).await?;
+    let base_fee = query_evm_rpc(http_client, &url, evm_rpc_headers, body).await?;
 
-    let af_portfolio: Vec<PortfolioEntry> = send_info_request(InfoRequest::Portfolio {
-        user: "0xfefefefefefefefefefefefefefefefefefefefe".to_string(),
-    })
-    .await?;
+    Ok((block_number, base_fee))
+}
+
+pub async fn get_protocol_data(
+    info_client: &InfoClient,
+) -> Result<(f64, usize, usize), ExporterError> {
+    let af_portfolio = info_client
+        .portfolio("0xfefefefefefefefefefefefefefefefefefefefe")
+        .await?;
 
     let daily_portfolio_entries = if let Some(daily_portfolio_entries) =
         af_portfolio.iter().find(|entry| entry.period == "day")
     {
         daily_portfolio_entries
     } else {
-        bail!("Couldn't find daily portfolio entries in the user details of the Assistance Fund!");
+        return Err(ExporterError::MissingField(
+            "Couldn't find daily portfolio entries in the user details of the Assistance Fund!"
+                .to_string(),
+        ));
     };
 
     let latest_account_value = if let Some(latest_account_value) = daily_portfolio_entries
@@ -139,33 +205,60 @@ pub async fn get_protocol_data(alchemy_key: &str) -> anyhow::Result<(u64, u64, f
     {
         latest_account_value
     } else {
-        bail!("Couldn't find find the latest account value of the Assistance Fund!");
+        return Err(ExporterError::MissingField(
+            "Couldn't find find the latest account value of the Assistance Fund!".to_string(),
+        ));
     };
 
     let af_account_value = latest_account_value.1.parse()?;
 
-    let num_spot_tokens = match send_info_request::<SpotMetaData>(InfoRequest::SpotMeta).await {
-        Ok(spot_tokens) => {
-            spot_tokens.tokens.len()
-        },
+    let num_spot_tokens = match info_client.spot_meta().await {
+        Ok(spot_tokens) => spot_tokens.tokens.len(),
         Err(e) => {
             error!("Error receiving SpotMeta: {e}");
             0
         }
     };
 
-    let perp_tokens: PerpMetaData = send_info_request(InfoRequest::Meta).await?;
+    let perp_tokens: PerpMetaData = info_client.meta().await?;
     let num_perp_tokens = perp_tokens
         .universe
         .iter()
         .filter(|item| item.is_delisted != Some(true))
         .count();
 
-    Ok((
-        block_number,
-        base_fee,
-        af_account_value,
-        num_spot_tokens,
-        num_perp_tokens,
-    ))
+    Ok((af_account_value, num_spot_tokens, num_perp_tokens))
+}
+
+pub async fn get_validator_data(info_client: &InfoClient) -> Result<ValidatorSet, ExporterError> {
+    let validator_summaries: Vec<ValidatorSummary> = info_client.validator_summaries().await?;
+
+    let mut validators = Vec::new();
+    for summary in validator_summaries.iter() {
+        let mut uptime_fraction = Vec::new();
+        for (period, stats) in summary.stats.iter() {
+            uptime_fraction.push((period.clone(), stats.uptime_fraction.parse()?));
+        }
+
+        validators.push(Validator {
+            address: summary.validator.clone(),
+            name: summary.name.clone(),
+            stake: summary.stake as f64 / 10_f64.powi(STAKE_DECIMALS),
+            is_jailed: summary.is_jailed,
+            is_active: summary.is_active,
+            commission: summary.commission.parse()?,
+            num_recent_blocks: summary.n_recent_blocks,
+            uptime_fraction,
+        });
+    }
+
+    let num_active = validators
+        .iter()
+        .filter(|validator| validator.is_active)
+        .count();
+
+    Ok(ValidatorSet {
+        num_active,
+        validators,
+    })
 }
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use prometheus::{Counter, Error, Gauge, Opts, Registry};
+use tokio::{sync::Mutex, time::Instant};
+
+use crate::utils::RateLimitConfig;
+
+/// Token bucket which is shared by all requests to the info API and refills with the
+/// configured weight budget per minute.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    bucket: Mutex<Bucket>,
+    remaining_weight: Gauge,
+    throttled_seconds_total: Counter,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate_limit_config: &RateLimitConfig) -> Result<Self, Error> {
+        let capacity = rate_limit_config.weight_per_minute as f64;
+        let remaining_weight = Gauge::with_opts(Opts::new(
+            "info_rate_limit_remaining_weight",
+            "The remaining request weight budget for the info API",
+        ))?;
+        remaining_weight.set(capacity);
+
+        Ok(Self {
+            capacity,
+            refill_per_sec: capacity / 60.0,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+            remaining_weight,
+            throttled_seconds_total: Counter::with_opts(Opts::new(
+                "info_rate_limit_throttled_seconds_total",
+                "The time in seconds requests to the info API waited for the rate limit",
+            ))?,
+        })
+    }
+
+    pub fn register(&self, registry: &Registry) -> Result<(), Error> {
+        registry.register(Box::new(self.remaining_weight.clone()))?;
+        registry.register(Box::new(self.throttled_seconds_total.clone()))?;
+
+        Ok(())
+    }
+
+    /// Waits until the bucket holds enough weight for the request and takes it. A budget
+    /// of zero disables the rate limiting.
+    pub async fn acquire(&self, weight: u32) {
+        if self.capacity <= 0.0 {
+            return;
+        }
+
+        let weight = (weight as f64).min(self.capacity);
+
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+                bucket.last_refill = now;
+
+                if bucket.tokens >= weight {
+                    bucket.tokens -= weight;
+                    self.remaining_weight.set(bucket.tokens);
+                    return;
+                }
+
+                self.remaining_weight.set(bucket.tokens);
+                Duration::from_secs_f64((weight - bucket.tokens) / self.refill_per_sec)
+            };
+
+            self.throttled_seconds_total.inc_by(wait.as_secs_f64());
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::OnceCell;
+
+use crate::error::ExporterError;
+
+type Flight = Arc<OnceCell<Result<String, Arc<ExporterError>>>>;
+
+/// Coalesces identical in-flight requests, so that concurrent callers with the same key
+/// share the response of a single upstream call.
+#[derive(Debug, Default)]
+pub struct SingleFlight {
+    in_flight: Mutex<HashMap<String, Flight>>,
+}
+
+impl SingleFlight {
+    pub async fn run<F, Fut>(&self, key: &str, f: F) -> Result<String, ExporterError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String, ExporterError>>,
+    {
+        let (flight, _leader_guard) = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            match in_flight.get(key) {
+                Some(flight) => (flight.clone(), None),
+                None => {
+                    let flight = Flight::default();
+                    in_flight.insert(key.to_string(), flight.clone());
+                    let leader_guard = LeaderGuard {
+                        single_flight: self,
+                        key,
+                        flight: flight.clone(),
+                    };
+                    (flight, Some(leader_guard))
+                }
+            }
+        };
+
+        // If the leader gets cancelled, one of the waiting callers runs its own request.
+        flight
+            .get_or_init(|| async { f().await.map_err(Arc::new) })
+            .await
+            .clone()
+            .map_err(ExporterError::Shared)
+    }
+}
+
+/// Removes the flight of the leader once it completed or got cancelled, so that later
+/// callers start a new upstream call.
+struct LeaderGuard<'a> {
+    single_flight: &'a SingleFlight,
+    key: &'a str,
+    flight: Flight,
+}
+
+impl Drop for LeaderGuard<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .single_flight
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if in_flight
+            .get(self.key)
+            .is_some_and(|flight| Arc::ptr_eq(flight, &self.flight))
+        {
+            in_flight.remove(self.key);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    error::ExporterError, privacy::AddressMask, protocol_meta::ValidatorSet,
+    user_details::UserDetails,
+};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FinancialMeta {
+    pub price: f64,
+    pub marketcap: i64,
+    pub fdv: i64,
+    pub tvl: i64,
+    pub circulating_supply: f64,
+    pub total_supply: f64,
+}
+
+impl From<(f64, i64, i64, i64, f64, f64)> for FinancialMeta {
+    fn from(financial_meta: (f64, i64, i64, i64, f64, f64)) -> Self {
+        Self {
+            price: financial_meta.0,
+            marketcap: financial_meta.1,
+            fdv: financial_meta.2,
+            tvl: financial_meta.3,
+            circulating_supply: financial_meta.4,
+            total_supply: financial_meta.5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EvmMeta {
+    pub block_number: u64,
+    pub base_fee: u64,
+}
+
+impl From<(u64, u64)> for EvmMeta {
+    fn from(evm_meta: (u64, u64)) -> Self {
+        Self {
+            block_number: evm_meta.0,
+            base_fee: evm_meta.1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProtocolMeta {
+    pub af_account_value: f64,
+    pub num_spot_tokens: usize,
+    pub num_perp_tokens: usize,
+}
+
+impl From<(f64, usize, usize)> for ProtocolMeta {
+    fn from(protocol_meta: (f64, usize, usize)) -> Self {
+        Self {
+            af_account_value: protocol_meta.0,
+            num_spot_tokens: protocol_meta.1,
+            num_perp_tokens: protocol_meta.2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VaultMeta {
+    pub value: f64,
+    pub pnl: f64,
+    pub apr: f64,
+    pub leader_fraction: f64,
+    pub leader_commission: f64,
+    pub num_followers: usize,
+    pub max_distributable: f64,
+    pub max_withdrawable: f64,
+    pub is_closed: bool,
+    pub allow_deposits: bool,
+}
+
+impl From<(f64, f64, f64, f64, f64, usize, f64, f64, bool, bool)> for VaultMeta {
+    fn from(vault_details: (f64, f64, f64, f64, f64, usize, f64, f64, bool, bool)) -> Self {
+        Self {
+            value: vault_details.0,
+            pnl: vault_details.1,
+            apr: vault_details.2,
+            leader_fraction: vault_details.3,
+            leader_commission: vault_details.4,
+            num_followers: vault_details.5,
+            max_distributable: vault_details.6,
+            max_withdrawable: vault_details.7,
+            is_closed: vault_details.8,
+            allow_deposits: vault_details.9,
+        }
+    }
+}
+
+/// The latest successfully collected data of a collector.
+#[derive(Debug)]
+pub struct SnapshotEntry<T> {
+    data: Option<T>,
+    fetched_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+impl<T> Default for SnapshotEntry<T> {
+    fn default() -> Self {
+        Self {
+            data: None,
+            fetched_at: None,
+            last_error: None,
+        }
+    }
+}
+
+impl<T: Clone> SnapshotEntry<T> {
+    pub fn record_success(&mut self, data: T) {
+        self.data = Some(data);
+        self.fetched_at = Some(Utc::now());
+        self.last_error = None;
+    }
+
+    /// Keeps the previous data, which is reported as stale from now on.
+    pub fn record_error(&mut self, error: &ExporterError) {
+        self.last_error = Some(error.to_string());
+    }
+
+    /// Drops the data of a collector which got disabled.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn view(&self, max_age: Duration) -> CollectorSnapshot<T> {
+        CollectorSnapshot {
+            data: self.data.clone(),
+            fetched_at: self.fetched_at.map(|fetched_at| fetched_at.to_rfc3339()),
+            stale: self.is_stale(max_age),
+            last_error: self.last_error.clone(),
+        }
+    }
+
+    /// Data is stale if there is none, if the last refresh failed or if it is older
+    /// than the maximum age, e.g. because there were no scrapes.
+    fn is_stale(&self, max_age: Duration) -> bool {
+        match self.fetched_at {
+            Some(fetched_at) => {
+                self.last_error.is_some()
+                    || (Utc::now() - fetched_at).to_std().unwrap_or_default() > max_age
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CollectorSnapshot<T> {
+    pub data: Option<T>,
+    pub fetched_at: Option<String>,
+    pub stale: bool,
+    pub last_error: Option<String>,
+}
+
+/// The latest data of all collectors, as served by the JSON API.
+#[derive(Debug, Default)]
+pub struct Snapshots {
+    pub coingecko: SnapshotEntry<FinancialMeta>,
+    pub evm: SnapshotEntry<EvmMeta>,
+    pub protocol: SnapshotEntry<ProtocolMeta>,
+    pub validator: SnapshotEntry<ValidatorSet>,
+    pub vault: SnapshotEntry<VaultMeta>,
+    pub user: SnapshotEntry<Vec<UserDetails>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SnapshotView {
+    pub coingecko: CollectorSnapshot<FinancialMeta>,
+    pub evm: CollectorSnapshot<EvmMeta>,
+    pub protocol: CollectorSnapshot<ProtocolMeta>,
+    pub validator: CollectorSnapshot<ValidatorSet>,
+    pub vault: CollectorSnapshot<VaultMeta>,
+    pub user: CollectorSnapshot<Vec<UserDetails>>,
+}
+
+impl Snapshots {
+    /// The snapshot of all collectors, with the user addresses shown like in the labels.
+    pub fn view(&self, max_age: Duration, address_mask: &AddressMask) -> SnapshotView {
+        let mut user = self.user.view(max_age);
+        if let Some(user_details) = &mut user.data {
+            for user_details in user_details.iter_mut() {
+                mask_user_details(user_details, address_mask);
+            }
+        }
+
+        SnapshotView {
+            coingecko: self.coingecko.view(max_age),
+            evm: self.evm.view(max_age),
+            protocol: self.protocol.view(max_age),
+            validator: self.validator.view(max_age),
+            vault: self.vault.view(max_age),
+            user,
+        }
+    }
+
+    /// The snapshot of a monitored account, the master account or one of its
+    /// sub-accounts, looked up by its address.
+    pub fn user(
+        &self,
+        address: &str,
+        max_age: Duration,
+        address_mask: &AddressMask,
+    ) -> Option<CollectorSnapshot<UserDetails>> {
+        let mut user_details = self
+            .user
+            .data
+            .as_ref()?
+            .iter()
+            .find(|user_details| user_details.address.eq_ignore_ascii_case(address))?
+            .clone();
+        mask_user_details(&mut user_details, address_mask);
+
+        Some(CollectorSnapshot {
+            data: Some(user_details),
+            fetched_at: self
+                .user
+                .fetched_at
+                .map(|fetched_at| fetched_at.to_rfc3339()),
+            stale: self.user.is_stale(max_age),
+            last_error: self.user.last_error.clone(),
+        })
+    }
+}
+
+fn mask_user_details(user_details: &mut UserDetails, address_mask: &AddressMask) {
+    user_details.address = address_mask.display(&user_details.address);
+    user_details.master = address_mask.display(&user_details.master);
+}
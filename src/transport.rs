@@ -0,0 +1,113 @@
+use std::{collections::HashMap, fmt::Debug, path::Path, pin::Pin};
+
+use reqwest::{Client, Request, Response, StatusCode};
+use serde_json::Value;
+
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Response, reqwest::Error>> + Send + 'a>>;
+
+/// Executes the HTTP requests of the `HttpClient`, which allows replacing the network in
+/// tests and offline tools.
+pub trait Transport: Debug + Send + Sync {
+    fn execute(&self, request: Request) -> TransportFuture<'_>;
+}
+
+/// Transport which sends the requests over the network.
+#[derive(Clone, Debug)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(self.client.execute(request))
+    }
+}
+
+/// Transport which replays recorded JSON responses instead of calling the upstream APIs.
+///
+/// Fixtures are looked up by the `type` of info requests (e.g. `vaultDetails`), the
+/// `method` of JSON-RPC requests (e.g. `eth_blockNumber`) or otherwise by the last
+/// segment of the URL path (e.g. `hyperliquid` for CoinGecko). Requests without a
+/// fixture are answered with a 404.
+#[derive(Clone, Debug, Default)]
+pub struct FixtureTransport {
+    fixtures: HashMap<String, String>,
+}
+
+impl FixtureTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads all `<key>.json` files of the directory as fixtures.
+    pub fn from_dir(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut fixture_transport = Self::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let key = path
+                .extension()
+                .filter(|extension| *extension == "json")
+                .and(path.file_stem())
+                .and_then(|stem| stem.to_str());
+            if let Some(key) = key {
+                let body = std::fs::read_to_string(&path)?;
+                fixture_transport = fixture_transport.with_fixture(key, body);
+            }
+        }
+
+        Ok(fixture_transport)
+    }
+
+    pub fn with_fixture(mut self, key: impl Into<String>, body: impl Into<String>) -> Self {
+        self.fixtures.insert(key.into(), body.into());
+        self
+    }
+
+    fn fixture_key(request: &Request) -> String {
+        let body: Option<Value> = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .and_then(|bytes| serde_json::from_slice(bytes).ok());
+
+        if let Some(body) = body {
+            if let Some(request_type) = body["type"].as_str() {
+                return request_type.to_string();
+            }
+            if let Some(method) = body["method"].as_str() {
+                return method.to_string();
+            }
+        }
+
+        request
+            .url()
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        let key = Self::fixture_key(&request);
+        let (status, body) = match self.fixtures.get(&key) {
+            Some(body) => (StatusCode::OK, body.clone()),
+            None => (StatusCode::NOT_FOUND, format!("No fixture for {key}")),
+        };
+
+        let response = http::Response::builder()
+            .status(status)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .expect("A fixture response is always valid");
+
+        Box::pin(async move { Ok(Response::from(response)) })
+    }
+}
@@ -1,44 +1,126 @@
-use crate::{
-    utils::{InfoRequest, send_info_request},
-    vault_details::PortfolioEntry,
-};
-use anyhow::bail;
+use crate::{error::ExporterError, info_client::InfoClient};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use tracing::{debug, error, warn};
 
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UserStakingSummary {
-    delegated: String,
-    undelegated: String,
-    total_pending_withdrawal: String,
-    n_pending_withdrawals: i64,
+    pub delegated: String,
+    pub undelegated: String,
+    pub total_pending_withdrawal: String,
+    pub n_pending_withdrawals: i64,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct OpenOrders {
-    coin: String,
-    limit_px: String,
-    oid: i64,
-    side: String,
-    sz: String,
-    timestamp: i64,
+pub struct Delegation {
+    pub validator: String,
+    pub amount: String,
+    pub locked_until_timestamp: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegatorReward {
+    pub time: u64,
+    pub source: String,
+    pub total_amount: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendOpenOrder {
+    pub coin: String,
+    pub limit_px: String,
+    pub oid: i64,
+    pub side: String,
+    pub sz: String,
+    pub timestamp: i64,
+    pub trigger_condition: String,
+    pub is_trigger: bool,
+    pub trigger_px: String,
+    pub is_position_tpsl: bool,
+    pub reduce_only: bool,
+    pub order_type: String,
+    pub orig_sz: String,
+    pub tif: Option<String>,
+    pub cloid: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StakingReward {
+    pub source: String,
+    pub amount: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StakingDelegation {
+    pub validator: String,
+    pub amount: f64,
+    /// The timestamp in milliseconds until the delegation is locked.
+    pub locked_until: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OpenOrderExposure {
+    pub market: String,
+    pub side: String,
+    pub notional: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OpenOrderGroup {
+    pub coin: String,
+    pub side: String,
+    pub order_type: String,
+    pub reduce_only: bool,
+    pub count: usize,
+    pub notional: f64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SubAccount {
+    pub name: String,
+    pub sub_account_user: String,
+    pub master: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UserDetails {
+    pub address: String,
+    pub master: String,
+    pub account_value: f64,
+    pub pnl: f64,
+    pub staking_delegated: f64,
+    pub staking_undelegated: f64,
+    pub staking_pending_withdrawal: f64,
+    pub staking_num_pending_withdrawals: i64,
+    pub staking_rewards: Vec<StakingReward>,
+    pub staking_delegations: Vec<StakingDelegation>,
+    pub num_open_orders: usize,
+    pub value_open_orders: f64,
+    pub open_order_exposures: Vec<OpenOrderExposure>,
+    pub open_order_groups: Vec<OpenOrderGroup>,
+    pub num_unparseable_open_orders: u64,
+    pub oldest_open_order_age: f64,
 }
 
 pub async fn get_user_details(
+    info_client: &InfoClient,
     user_address: String,
-) -> anyhow::Result<(f64, f64, f64, f64, f64, usize, f64)> {
-    let user_portfolio: Vec<PortfolioEntry> = send_info_request(InfoRequest::Portfolio {
-        user: user_address.clone(),
-    })
-    .await?;
+) -> Result<UserDetails, ExporterError> {
+    let user_portfolio = info_client.portfolio(&user_address).await?;
 
     let daily_portfolio_entries = if let Some(daily_portfolio_entries) =
         user_portfolio.iter().find(|entry| entry.period == "day")
     {
         daily_portfolio_entries
     } else {
-        bail!("Couldn't find daily portfolio entries in the user details!");
+        return Err(ExporterError::MissingField(
+            "Couldn't find daily portfolio entries in the user details!".to_string(),
+        ));
     };
 
     let latest_account_value = if let Some(latest_account_value) = daily_portfolio_entries
@@ -49,7 +131,9 @@ pub async fn get_user_details(
     {
         latest_account_value
     } else {
-        bail!("Couldn't find find the latest account value of the user!");
+        return Err(ExporterError::MissingField(
+            "Couldn't find find the latest account value of the user!".to_string(),
+        ));
     };
 
     let latest_pnl = if let Some(latest_pnl) = daily_portfolio_entries
@@ -60,40 +144,211 @@ pub async fn get_user_details(
     {
         latest_pnl
     } else {
-        bail!("Couldn't find find the latest PnL of the user!");
+        return Err(ExporterError::MissingField(
+            "Couldn't find find the latest PnL of the user!".to_string(),
+        ));
     };
 
-    let user_staking_summary: UserStakingSummary =
-        send_info_request(InfoRequest::DelegatorSummary {
-            user: user_address.clone(),
-        })
-        .await?;
+    let user_staking_summary = info_client.delegator_summary(&user_address).await?;
 
-    let user_open_orders: Vec<OpenOrders> =
-        send_info_request(InfoRequest::OpenOrders { user: user_address }).await?;
+    let user_delegations = info_client.delegations(&user_address).await?;
+
+    let user_delegator_rewards = info_client.delegator_rewards(&user_address).await?;
+
+    let user_open_orders = info_client.frontend_open_orders(&user_address).await?;
 
     let user_account_value = latest_account_value.1.parse()?;
     let user_pnl = latest_pnl.1.parse()?;
     let user_staking_delegated = user_staking_summary.delegated.parse()?;
     let user_staking_undelegated = user_staking_summary.undelegated.parse()?;
     let user_staking_pending_withdrawal = user_staking_summary.total_pending_withdrawal.parse()?;
+    let user_staking_num_pending_withdrawals = user_staking_summary.n_pending_withdrawals;
+
+    let mut user_staking_rewards: Vec<StakingReward> = Vec::new();
+    for reward in user_delegator_rewards.iter() {
+        let amount: f64 = reward.total_amount.parse()?;
+        match user_staking_rewards
+            .iter_mut()
+            .find(|staking_reward| staking_reward.source == reward.source)
+        {
+            Some(staking_reward) => staking_reward.amount += amount,
+            None => user_staking_rewards.push(StakingReward {
+                source: reward.source.clone(),
+                amount,
+            }),
+        }
+    }
+
+    let mut user_staking_delegations: Vec<StakingDelegation> = Vec::new();
+    for delegation in user_delegations.iter() {
+        user_staking_delegations.push(StakingDelegation {
+            validator: delegation.validator.clone(),
+            amount: delegation.amount.parse()?,
+            locked_until: delegation.locked_until_timestamp,
+        });
+    }
+
+    let perp_meta = info_client.meta().await?;
+    let spot_meta = info_client.spot_meta().await?;
+
     let user_num_open_orders = user_open_orders.len();
-    let user_value_open_orders = user_open_orders
-        .iter()
-        .filter_map(|order| {
-            let px = order.limit_px.parse::<f64>().ok()?;
-            let sz = order.sz.parse::<f64>().ok()?;
-            Some(px * sz)
-        })
-        .sum();
-
-    Ok((
-        user_account_value,
-        user_pnl,
-        user_staking_delegated,
-        user_staking_undelegated,
-        user_staking_pending_withdrawal,
-        user_num_open_orders,
-        user_value_open_orders,
-    ))
+    let mut user_value_open_orders = 0.0;
+    let mut user_open_order_exposures: Vec<OpenOrderExposure> = Vec::new();
+    let mut user_open_order_groups: Vec<OpenOrderGroup> = Vec::new();
+    let mut user_num_unparseable_open_orders = 0;
+    for order in user_open_orders.iter() {
+        // Orders which can't be valued are still counted in their group, so that the
+        // groups add up to the number of open orders.
+        let notional = match (order.limit_px.parse::<f64>(), order.sz.parse::<f64>()) {
+            (Ok(px), Ok(sz)) => Some(px * sz),
+            _ => {
+                warn!(
+                    "Couldn't parse price {} and size {} of open order {}",
+                    order.limit_px, order.sz, order.oid
+                );
+                user_num_unparseable_open_orders += 1;
+                None
+            }
+        };
+        let side = match order.side.as_str() {
+            "B" => "buy",
+            "A" => "sell",
+            other => other,
+        };
+        let market = if perp_meta
+            .universe
+            .iter()
+            .any(|item| item.name == order.coin)
+        {
+            "perp"
+        } else if order.coin.starts_with('@')
+            || spot_meta
+                .universe
+                .iter()
+                .any(|pair| pair.name == order.coin)
+        {
+            "spot"
+        } else {
+            "unknown"
+        };
+
+        if let Some(notional) = notional {
+            user_value_open_orders += notional;
+
+            match user_open_order_exposures
+                .iter_mut()
+                .find(|exposure| exposure.market == market && exposure.side == side)
+            {
+                Some(exposure) => exposure.notional += notional,
+                None => user_open_order_exposures.push(OpenOrderExposure {
+                    market: market.to_string(),
+                    side: side.to_string(),
+                    notional,
+                }),
+            }
+        }
+        let notional = notional.unwrap_or_default();
+
+        match user_open_order_groups.iter_mut().find(|group| {
+            group.coin == order.coin
+                && group.side == side
+                && group.order_type == order.order_type
+                && group.reduce_only == order.reduce_only
+        }) {
+            Some(group) => {
+                group.count += 1;
+                group.notional += notional;
+            }
+            None => user_open_order_groups.push(OpenOrderGroup {
+                coin: order.coin.clone(),
+                side: side.to_string(),
+                order_type: order.order_type.clone(),
+                reduce_only: order.reduce_only,
+                count: 1,
+                notional,
+            }),
+        }
+    }
+
+    let user_oldest_open_order_age =
+        match user_open_orders.iter().map(|order| order.timestamp).min() {
+            Some(oldest_timestamp) => {
+                (Utc::now().timestamp_millis() - oldest_timestamp).max(0) as f64 / 1000.0
+            }
+            None => 0.0,
+        };
+
+    Ok(UserDetails {
+        address: user_address.clone(),
+        master: user_address,
+        account_value: user_account_value,
+        pnl: user_pnl,
+        staking_delegated: user_staking_delegated,
+        staking_undelegated: user_staking_undelegated,
+        staking_pending_withdrawal: user_staking_pending_withdrawal,
+        staking_num_pending_withdrawals: user_staking_num_pending_withdrawals,
+        staking_rewards: user_staking_rewards,
+        staking_delegations: user_staking_delegations,
+        num_open_orders: user_num_open_orders,
+        value_open_orders: user_value_open_orders,
+        open_order_exposures: user_open_order_exposures,
+        open_order_groups: user_open_order_groups,
+        num_unparseable_open_orders: user_num_unparseable_open_orders,
+        oldest_open_order_age: user_oldest_open_order_age,
+    })
+}
+
+/// The details of a master account and its sub-accounts.
+#[derive(Debug, Default)]
+pub struct AccountDetails {
+    pub accounts: Vec<UserDetails>,
+    /// The errors of the sub-account discovery and of single sub-accounts, whose details
+    /// are missing from `accounts`.
+    pub errors: Vec<ExporterError>,
+}
+
+impl AccountDetails {
+    /// Whether all sub-accounts were collected, so that the totals of the master account
+    /// don't undercount.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Collects the details of the master account and its sub-accounts. Only a failure of
+/// the master account fails the collection, the failures of sub-accounts are reported in
+/// `AccountDetails::errors` along with the accounts which were collected.
+pub async fn get_account_details(
+    info_client: &InfoClient,
+    master_address: String,
+) -> Result<AccountDetails, ExporterError> {
+    let mut errors = Vec::new();
+    let sub_accounts = match info_client.sub_accounts(&master_address).await {
+        Ok(sub_accounts) => sub_accounts,
+        Err(e) => {
+            error!("Failed to discover the sub-accounts: {e}");
+            errors.push(e);
+            Vec::new()
+        }
+    };
+
+    let mut accounts = vec![get_user_details(info_client, master_address.clone()).await?];
+    for sub_account in sub_accounts {
+        debug!("Querying user details for sub-account {}", sub_account.name);
+        match get_user_details(info_client, sub_account.sub_account_user.clone()).await {
+            Ok(mut sub_account_details) => {
+                sub_account_details.master = master_address.clone();
+                accounts.push(sub_account_details);
+            }
+            Err(e) => {
+                error!(
+                    "Failed receive the user details of sub-account {}: {e}",
+                    sub_account.name
+                );
+                errors.push(e);
+            }
+        }
+    }
+
+    Ok(AccountDetails { accounts, errors })
 }
@@ -1,8 +1,26 @@
-use crate::MAINNET_INFO_API_URL;
-use anyhow::Context;
-use reqwest::Client;
+use crate::{
+    consts::{
+        ALCHEMY_API_URL, ALCHEMY_TESTNET_API_URL, COINGECKO_CACHE_KEY, COINGECKO_HL_API_URL,
+        MAINNET_INFO_API_URL, TESTNET_INFO_API_URL,
+    },
+    privacy::{AddressMask, REDACTED, Secret},
+};
+use anyhow::{Context, anyhow, bail};
+use bcrypt::HashParts;
+use reqwest::{
+    Url,
+    header::{HeaderName, HeaderValue},
+};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Prefix of the environment variables which override the fields of the config file.
+pub const ENV_PREFIX: &str = "HYPURR_";
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "type")]
@@ -18,52 +36,640 @@ pub enum InfoRequest {
     DelegatorSummary {
         user: String,
     },
-    OpenOrders {
+    Delegations {
+        user: String,
+    },
+    DelegatorRewards {
+        user: String,
+    },
+    FrontendOpenOrders {
+        user: String,
+    },
+    SubAccounts {
         user: String,
     },
     SpotMeta,
     Meta,
+    ValidatorSummaries,
+}
+
+impl InfoRequest {
+    /// The `type`s of all requests, which are the keys of their TTLs in `[cache]`.
+    pub const REQUEST_TYPES: [&'static str; 10] = [
+        "vaultDetails",
+        "portfolio",
+        "delegatorSummary",
+        "delegations",
+        "delegatorRewards",
+        "frontendOpenOrders",
+        "subAccounts",
+        "spotMeta",
+        "meta",
+        "validatorSummaries",
+    ];
+
+    /// The `type` of the request as sent to the info API.
+    pub fn request_type(&self) -> &'static str {
+        match self {
+            InfoRequest::VaultDetails { .. } => "vaultDetails",
+            InfoRequest::Portfolio { .. } => "portfolio",
+            InfoRequest::DelegatorSummary { .. } => "delegatorSummary",
+            InfoRequest::Delegations { .. } => "delegations",
+            InfoRequest::DelegatorRewards { .. } => "delegatorRewards",
+            InfoRequest::FrontendOpenOrders { .. } => "frontendOpenOrders",
+            InfoRequest::SubAccounts { .. } => "subAccounts",
+            InfoRequest::SpotMeta => "spotMeta",
+            InfoRequest::Meta => "meta",
+            InfoRequest::ValidatorSummaries => "validatorSummaries",
+        }
+    }
+
+    /// The serialized request which identifies its response in the cache.
+    pub fn cache_key(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| format!("{self:?}"))
+    }
+
+    /// The weight of the request against the IP rate limit of the info API, see
+    /// https://hyperliquid.gitbook.io/hyperliquid-docs/for-developers/api/rate-limits-and-user-limits
+    pub fn weight(&self) -> u32 {
+        match self {
+            InfoRequest::VaultDetails { .. } => 20,
+            InfoRequest::Portfolio { .. } => 20,
+            InfoRequest::DelegatorSummary { .. } => 20,
+            InfoRequest::Delegations { .. } => 20,
+            InfoRequest::DelegatorRewards { .. } => 20,
+            InfoRequest::FrontendOpenOrders { .. } => 20,
+            InfoRequest::SubAccounts { .. } => 20,
+            InfoRequest::SpotMeta => 20,
+            InfoRequest::Meta => 20,
+            InfoRequest::ValidatorSummaries => 20,
+        }
+    }
 }
 
-pub async fn send_info_request<T: for<'a> Deserialize<'a>>(
-    info_request: InfoRequest,
-) -> anyhow::Result<T> {
-    let http_client = Client::new();
-    let url = MAINNET_INFO_API_URL.to_string();
-    let data = serde_json::to_string(&info_request).context(format!(
-        "Failed to deserialize the info_request {:?}",
-        info_request
-    ))?;
-
-    let response = http_client
-        .post(url.to_string())
-        .header("Content-Type", "application/json")
-        .body(data.clone())
-        .send()
-        .await?;
-    debug!(
-        "Received response with status {} for request {}",
-        response.status(),
-        data
-    );
-
-    let json_response = response
-        .json()
-        .await
-        .context("Failed to deserialize the response body as JSON")?;
-
-    Ok(json_response)
-}
-
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+        }
+    }
+
+    pub fn info_api_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => MAINNET_INFO_API_URL,
+            Network::Testnet => TESTNET_INFO_API_URL,
+        }
+    }
+
+    pub fn alchemy_api_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => ALCHEMY_API_URL,
+            Network::Testnet => ALCHEMY_TESTNET_API_URL,
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            other => Err(anyhow!("Unknown network {other}")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
+    #[serde(default = "default_listen_address")]
+    pub listen_address: String,
+    #[serde(default = "default_metrics_path")]
+    pub metrics_path: String,
     pub user_address: Option<String>,
     pub vault_address: Option<String>,
-    pub coingecko_key: Option<String>,
-    pub alchemy_key: Option<String>,
+    pub coingecko_key: Option<Secret>,
+    pub alchemy_key: Option<Secret>,
+    #[serde(default)]
+    pub network: Network,
+    pub info_api_url: Option<String>,
+    pub coingecko_api_url: Option<String>,
+    pub evm_rpc_url: Option<String>,
+    #[serde(default)]
+    pub evm_rpc_headers: HashMap<String, Secret>,
+    #[serde(default = "default_max_concurrent_probes")]
+    pub max_concurrent_probes: usize,
+    #[serde(default = "default_snapshot_max_age_secs")]
+    pub snapshot_max_age_secs: u64,
+    #[serde(default)]
+    pub mask_addresses: bool,
+    #[serde(default)]
+    pub address_aliases: HashMap<String, String>,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub web: WebConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    pub connect_timeout_secs: u64,
+    pub request_timeout_secs: u64,
+    pub user_agent: String,
+    pub proxy: Option<String>,
+    pub pool_idle_timeout_secs: u64,
+    pub pool_max_idle_per_host: usize,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 5,
+            request_timeout_secs: 15,
+            user_agent: format!("hypurr-exporter/{}", env!("CARGO_PKG_VERSION")),
+            proxy: None,
+            pool_idle_timeout_secs: 90,
+            pool_max_idle_per_host: 8,
+        }
+    }
 }
 
-pub async fn read_config() -> anyhow::Result<Config> {
-    let content = std::fs::read_to_string("config.toml")?;
-    Ok(toml::from_str(&content)?)
+fn default_listen_address() -> String {
+    "0.0.0.0:3000".to_string()
+}
+
+fn default_metrics_path() -> String {
+    "/metrics".to_string()
+}
+
+fn default_max_concurrent_probes() -> usize {
+    4
+}
+
+fn default_snapshot_max_age_secs() -> u64 {
+    300
+}
+
+impl Config {
+    /// Overrides the fields of the config with the `HYPURR_*` environment variables, e.g.
+    /// `HYPURR_COINGECKO_KEY` or `HYPURR_HTTP_REQUEST_TIMEOUT_SECS` for `[http]`.
+    /// Instead of the value itself, each variable can also name a file containing the
+    /// value with the `_FILE` suffix, e.g. `HYPURR_COINGECKO_KEY_FILE`.
+    pub fn apply_env_overrides(&mut self) -> anyhow::Result<()> {
+        override_value(&mut self.listen_address, "LISTEN_ADDRESS")?;
+        override_value(&mut self.metrics_path, "METRICS_PATH")?;
+        override_option(&mut self.user_address, "USER_ADDRESS")?;
+        override_option(&mut self.vault_address, "VAULT_ADDRESS")?;
+        override_option(&mut self.coingecko_key, "COINGECKO_KEY")?;
+        override_option(&mut self.alchemy_key, "ALCHEMY_KEY")?;
+        override_value(&mut self.network, "NETWORK")?;
+        override_option(&mut self.info_api_url, "INFO_API_URL")?;
+        override_option(&mut self.coingecko_api_url, "COINGECKO_API_URL")?;
+        override_option(&mut self.evm_rpc_url, "EVM_RPC_URL")?;
+        override_map(&mut self.evm_rpc_headers, "EVM_RPC_HEADERS")?;
+        override_value(&mut self.max_concurrent_probes, "MAX_CONCURRENT_PROBES")?;
+        override_value(&mut self.snapshot_max_age_secs, "SNAPSHOT_MAX_AGE_SECS")?;
+        override_value(&mut self.mask_addresses, "MASK_ADDRESSES")?;
+        override_map(&mut self.address_aliases, "ADDRESS_ALIASES")?;
+
+        override_value(
+            &mut self.http.connect_timeout_secs,
+            "HTTP_CONNECT_TIMEOUT_SECS",
+        )?;
+        override_value(
+            &mut self.http.request_timeout_secs,
+            "HTTP_REQUEST_TIMEOUT_SECS",
+        )?;
+        override_value(&mut self.http.user_agent, "HTTP_USER_AGENT")?;
+        override_option(&mut self.http.proxy, "HTTP_PROXY")?;
+        override_value(
+            &mut self.http.pool_idle_timeout_secs,
+            "HTTP_POOL_IDLE_TIMEOUT_SECS",
+        )?;
+        override_value(
+            &mut self.http.pool_max_idle_per_host,
+            "HTTP_POOL_MAX_IDLE_PER_HOST",
+        )?;
+
+        override_value(&mut self.retry.attempts, "RETRY_ATTEMPTS")?;
+        override_value(&mut self.retry.base_delay_ms, "RETRY_BASE_DELAY_MS")?;
+        override_value(&mut self.retry.max_delay_ms, "RETRY_MAX_DELAY_MS")?;
+        override_list(
+            &mut self.retry.retryable_statuses,
+            "RETRY_RETRYABLE_STATUSES",
+        )?;
+
+        override_value(
+            &mut self.rate_limit.weight_per_minute,
+            "RATE_LIMIT_WEIGHT_PER_MINUTE",
+        )?;
+
+        override_map(&mut self.cache.ttl_secs, "CACHE_TTL_SECS")?;
+
+        let mut tls_server_config = self.web.tls_server_config.clone().unwrap_or_default();
+        override_value(
+            &mut tls_server_config.cert_file,
+            "WEB_TLS_SERVER_CONFIG_CERT_FILE",
+        )?;
+        override_value(
+            &mut tls_server_config.key_file,
+            "WEB_TLS_SERVER_CONFIG_KEY_FILE",
+        )?;
+        if tls_server_config != TlsServerConfig::default() {
+            self.web.tls_server_config = Some(tls_server_config);
+        }
+        override_map(&mut self.web.basic_auth_users, "WEB_BASIC_AUTH_USERS")?;
+        override_list(&mut self.web.bearer_tokens, "WEB_BEARER_TOKENS")?;
+
+        Ok(())
+    }
+
+    /// The info API endpoint, falling back to the preset of the configured network.
+    pub fn info_api_url(&self) -> String {
+        self.info_api_url
+            .clone()
+            .unwrap_or_else(|| self.network.info_api_url().to_string())
+    }
+
+    pub fn coingecko_api_url(&self) -> String {
+        self.coingecko_api_url
+            .clone()
+            .unwrap_or_else(|| COINGECKO_HL_API_URL.to_string())
+    }
+
+    /// The HyperEVM JSON-RPC endpoint, either configured explicitly (e.g. an own node)
+    /// or built from the Alchemy preset of the configured network and the Alchemy key.
+    pub fn evm_rpc_url(&self) -> Option<String> {
+        match (&self.evm_rpc_url, &self.alchemy_key) {
+            (Some(evm_rpc_url), _) => Some(evm_rpc_url.clone()),
+            (None, Some(alchemy_key)) => Some(format!(
+                "{}{}",
+                self.network.alchemy_api_url(),
+                alchemy_key.expose()
+            )),
+            (None, None) => None,
+        }
+    }
+
+    /// Shows the addresses in logs and labels as configured by `mask_addresses` and
+    /// `address_aliases`.
+    pub fn address_mask(&self) -> AddressMask {
+        AddressMask::new(self.mask_addresses, &self.address_aliases)
+    }
+
+    /// Checks the semantics which serde can't, reporting all problems at once.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut errors = Vec::new();
+
+        for (field, address) in [
+            ("user_address", &self.user_address),
+            ("vault_address", &self.vault_address),
+        ] {
+            if let Some(address) = address.as_ref().filter(|a| !is_valid_address(a)) {
+                errors.push(format!(
+                    "{field} {address:?} is not an address of 0x and 40 hex characters"
+                ));
+            }
+        }
+
+        for address in self
+            .address_aliases
+            .keys()
+            .filter(|address| !is_valid_address(address))
+        {
+            errors.push(format!(
+                "address_aliases key {address:?} is not an address of 0x and 40 hex characters"
+            ));
+        }
+
+        for (name, value) in self.evm_rpc_headers.iter() {
+            if HeaderName::from_bytes(name.as_bytes()).is_err()
+                || HeaderValue::from_str(value.expose()).is_err()
+            {
+                errors.push(format!(
+                    "evm_rpc_headers {name:?} is not a valid HTTP header"
+                ));
+            }
+        }
+
+        for (field, url) in [
+            ("info_api_url", &self.info_api_url),
+            ("coingecko_api_url", &self.coingecko_api_url),
+            ("evm_rpc_url", &self.evm_rpc_url),
+            ("http.proxy", &self.http.proxy),
+        ] {
+            let Some(url) = url else {
+                continue;
+            };
+            if let Err(e) = Url::parse(url) {
+                errors.push(format!("{field} {url:?} is not a valid URL: {e}"));
+            }
+        }
+
+        for (field, timeout_secs) in [
+            ("http.connect_timeout_secs", self.http.connect_timeout_secs),
+            ("http.request_timeout_secs", self.http.request_timeout_secs),
+        ] {
+            if timeout_secs == 0 {
+                errors.push(format!("{field} must not be zero"));
+            }
+        }
+
+        for request_type in self.cache.ttl_secs.keys().filter(|request_type| {
+            request_type.as_str() != COINGECKO_CACHE_KEY
+                && !InfoRequest::REQUEST_TYPES.contains(&request_type.as_str())
+        }) {
+            errors.push(format!(
+                "cache.ttl_secs key {request_type:?} is neither an info request type nor {COINGECKO_CACHE_KEY:?}"
+            ));
+        }
+
+        if let Some(tls_server_config) = &self.web.tls_server_config {
+            for (field, path) in [
+                ("cert_file", &tls_server_config.cert_file),
+                ("key_file", &tls_server_config.key_file),
+            ] {
+                if path.as_os_str().is_empty() {
+                    errors.push(format!("web.tls_server_config.{field} is missing"));
+                }
+            }
+        }
+
+        for (user, hash) in self.web.basic_auth_users.iter() {
+            if hash.expose().parse::<HashParts>().is_err() {
+                errors.push(format!(
+                    "web.basic_auth_users {user:?} is not a bcrypt hash"
+                ));
+            }
+        }
+
+        if self
+            .web
+            .bearer_tokens
+            .iter()
+            .any(|token| token.expose().is_empty())
+        {
+            errors.push("web.bearer_tokens must not be empty strings".to_string());
+        }
+
+        if !self.metrics_path.starts_with('/') {
+            errors.push(format!(
+                "metrics_path {:?} must start with a slash",
+                self.metrics_path
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            bail!("Invalid config:\n  - {}", errors.join("\n  - "))
+        }
+    }
+
+    /// A copy of the config with the credentials in URLs replaced, so that it can be
+    /// printed or logged. The `Secret` fields are redacted on their own.
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        for url in [
+            &mut config.info_api_url,
+            &mut config.coingecko_api_url,
+            &mut config.evm_rpc_url,
+            &mut config.http.proxy,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *url = redact_url(url, self.alchemy_key.as_ref().map(Secret::expose));
+        }
+
+        config
+    }
+}
+
+/// Whether the address is a hex encoded 20 byte address with a `0x` prefix.
+pub fn is_valid_address(address: &str) -> bool {
+    address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Removes the password of the URL and the Alchemy key, which is part of its path.
+fn redact_url(url: &str, alchemy_key: Option<&str>) -> String {
+    let mut url = match Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return REDACTED.to_string(),
+    };
+    if url.password().is_some() {
+        // The password is percent-encoded, so the marker is spelled without brackets.
+        let _ = url.set_password(Some("redacted"));
+    }
+
+    let url = url.to_string();
+    match alchemy_key {
+        Some(alchemy_key) if !alchemy_key.is_empty() => url.replace(alchemy_key, REDACTED),
+        _ => url,
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub retryable_statuses: Vec<u16>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            base_delay_ms: 250,
+            max_delay_ms: 10_000,
+            retryable_statuses: vec![429, 500, 502, 503, 504],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub weight_per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            weight_per_minute: 1200,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// TTL in seconds per request type, e.g. `spotMeta`, or of the CoinGecko data with the
+    /// `coingecko` key. Request types without a TTL aren't cached.
+    pub ttl_secs: HashMap<String, u64>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: HashMap::from([
+                ("spotMeta".to_string(), 300),
+                ("meta".to_string(), 300),
+                ("validatorSummaries".to_string(), 60),
+                ("vaultDetails".to_string(), 60),
+                (COINGECKO_CACHE_KEY.to_string(), 60),
+            ]),
+        }
+    }
+}
+
+/// Protects the endpoints of the exporter like the Prometheus `web-config.yml` does.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebConfig {
+    /// Serves HTTPS instead of HTTP if set.
+    pub tls_server_config: Option<TlsServerConfig>,
+    /// bcrypt hashes of the passwords by user name, e.g. from `htpasswd -nBC 10 ""`.
+    pub basic_auth_users: HashMap<String, Secret>,
+    /// Tokens which are accepted in an `Authorization: Bearer` header.
+    pub bearer_tokens: Vec<Secret>,
+}
+
+impl WebConfig {
+    /// Whether requests need to authenticate by basic auth or a bearer token.
+    pub fn requires_auth(&self) -> bool {
+        !self.basic_auth_users.is_empty() || !self.bearer_tokens.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsServerConfig {
+    /// PEM file with the certificate chain, which is reloaded when it changes.
+    pub cert_file: PathBuf,
+    /// PEM file with the private key, which is reloaded when it changes.
+    pub key_file: PathBuf,
+}
+
+/// Reads the config file and applies the environment overrides. A missing config file
+/// is only accepted if `required` is false, all fields are then read from the environment.
+pub async fn read_config(path: &Path, required: bool) -> anyhow::Result<Config> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => String::new(),
+        Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+    };
+    let mut config: Config =
+        toml::from_str(&content).context(format!("Failed to parse {}", path.display()))?;
+    config.apply_env_overrides()?;
+
+    Ok(config)
+}
+
+/// Reads the environment variable `HYPURR_<name>`, or the content of the file named by
+/// `HYPURR_<name>_FILE`, e.g. a mounted Kubernetes secret.
+fn env_var(name: &str) -> anyhow::Result<Option<String>> {
+    let name = format!("{ENV_PREFIX}{name}");
+    if let Ok(value) = std::env::var(&name) {
+        return Ok(Some(value));
+    }
+
+    let file_name = format!("{name}_FILE");
+    match std::env::var(&file_name) {
+        Ok(path) => {
+            let value = std::fs::read_to_string(&path)
+                .context(format!("Failed to read {path} of {file_name}"))?;
+            Ok(Some(value.trim().to_string()))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+fn parse_env_value<T: FromStr>(name: &str, value: &str) -> anyhow::Result<T>
+where
+    T::Err: Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Invalid value {value:?} of {ENV_PREFIX}{name}: {e}"))
+}
+
+fn override_value<T: FromStr>(field: &mut T, name: &str) -> anyhow::Result<()>
+where
+    T::Err: Display,
+{
+    if let Some(value) = env_var(name)? {
+        *field = parse_env_value(name, &value)?;
+    }
+
+    Ok(())
+}
+
+fn override_option<T: FromStr>(field: &mut Option<T>, name: &str) -> anyhow::Result<()>
+where
+    T::Err: Display,
+{
+    if let Some(value) = env_var(name)? {
+        *field = Some(parse_env_value(name, &value)?);
+    }
+
+    Ok(())
+}
+
+/// Overrides a list with comma separated values, e.g. `429,503`.
+fn override_list<T: FromStr>(field: &mut Vec<T>, name: &str) -> anyhow::Result<()>
+where
+    T::Err: Display,
+{
+    if let Some(value) = env_var(name)? {
+        *field = value
+            .split(',')
+            .filter(|item| !item.trim().is_empty())
+            .map(|item| parse_env_value(name, item))
+            .collect::<anyhow::Result<_>>()?;
+    }
+
+    Ok(())
+}
+
+/// Overrides a table with comma separated `key=value` pairs, e.g. `meta=300,spotMeta=300`.
+fn override_map<T: FromStr>(field: &mut HashMap<String, T>, name: &str) -> anyhow::Result<()>
+where
+    T::Err: Display,
+{
+    if let Some(value) = env_var(name)? {
+        let mut map = HashMap::new();
+        for pair in value.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or_else(|| {
+                anyhow!("Invalid pair {pair:?} of {ENV_PREFIX}{name}, expected key=value")
+            })?;
+            map.insert(key.trim().to_string(), parse_env_value(name, value)?);
+        }
+        *field = map;
+    }
+
+    Ok(())
 }
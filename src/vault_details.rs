@@ -1,5 +1,4 @@
-use crate::utils::{InfoRequest, send_info_request};
-use anyhow::bail;
+use crate::{error::ExporterError, info_client::InfoClient};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -63,13 +62,15 @@ pub struct RelationshipData {
     pub child_addresses: Vec<String>,
 }
 
+/// The value, PnL, APR, leader fraction, leader commission, number of followers, maximum
+/// distributable and withdrawable amounts, and the closed and deposits flags of a vault.
+pub type VaultMetrics = (f64, f64, f64, f64, f64, usize, f64, f64, bool, bool);
+
 pub async fn get_vault_details(
+    info_client: &InfoClient,
     vault_address: &str,
-) -> anyhow::Result<(f64, f64, f64, f64, f64, usize, f64, f64, bool, bool)> {
-    let vault_details: VaultDetails = send_info_request(InfoRequest::VaultDetails {
-        vault_address: vault_address.to_string(),
-    })
-    .await?;
+) -> Result<VaultMetrics, ExporterError> {
+    let vault_details = info_client.vault_details(vault_address).await?;
 
     let daily_portfolio_entries = if let Some(daily_portfolio_entries) = vault_details
         .portfolio
@@ -78,7 +79,9 @@ pub async fn get_vault_details(
     {
         daily_portfolio_entries
     } else {
-        bail!("Couldn't find daily portfolio entries in the vault details!");
+        return Err(ExporterError::MissingField(
+            "Couldn't find daily portfolio entries in the vault details!".to_string(),
+        ));
     };
 
     let latest_account_value = if let Some(latest_account_value) = daily_portfolio_entries
@@ -89,7 +92,9 @@ pub async fn get_vault_details(
     {
         latest_account_value
     } else {
-        bail!("Couldn't find find the latest account value of the vault!");
+        return Err(ExporterError::MissingField(
+            "Couldn't find find the latest account value of the vault!".to_string(),
+        ));
     };
 
     let latest_pnl = if let Some(latest_pnl) = daily_portfolio_entries
@@ -100,7 +105,9 @@ pub async fn get_vault_details(
     {
         latest_pnl
     } else {
-        bail!("Couldn't find find the latest PnL of the vault!");
+        return Err(ExporterError::MissingField(
+            "Couldn't find find the latest PnL of the vault!".to_string(),
+        ));
     };
 
     let vault_value = latest_account_value.1.parse()?;
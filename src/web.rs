@@ -0,0 +1,144 @@
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Context;
+use axum_server::tls_rustls::RustlsConfig;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use subtle::ConstantTimeEq;
+use tracing::{error, info};
+
+use crate::utils::{TlsServerConfig, WebConfig};
+
+/// How often the certificate and key files are checked for changes.
+const TLS_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Checks the `Authorization` header of requests against the `basic_auth_users` and
+/// `bearer_tokens` of the web config.
+#[derive(Debug, Default)]
+pub struct Authenticator {
+    /// Basic auth credentials which matched their bcrypt hash, as bcrypt is too slow to
+    /// run on every scrape. Only successful checks are cached, keyed by the header and
+    /// the hash, so that a changed password takes effect on a reload.
+    verified: Mutex<HashSet<(String, String)>>,
+}
+
+impl Authenticator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the request is authorized. Without users and tokens, all requests are.
+    pub async fn is_authorized(&self, web_config: &WebConfig, authorization: Option<&str>) -> bool {
+        if !web_config.requires_auth() {
+            return true;
+        }
+        let Some((scheme, credentials)) = authorization.and_then(|value| value.split_once(' '))
+        else {
+            return false;
+        };
+
+        if scheme.eq_ignore_ascii_case("bearer") {
+            let token = credentials.trim().as_bytes();
+            return web_config
+                .bearer_tokens
+                .iter()
+                .any(|expected| bool::from(token.ct_eq(expected.expose().as_bytes())));
+        }
+        if !scheme.eq_ignore_ascii_case("basic") {
+            return false;
+        }
+
+        let Some((user, password)) = BASE64_STANDARD
+            .decode(credentials.trim())
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|decoded| {
+                decoded
+                    .split_once(':')
+                    .map(|(user, password)| (user.to_string(), password.to_string()))
+            })
+        else {
+            return false;
+        };
+        let Some(hash) = web_config.basic_auth_users.get(&user) else {
+            return false;
+        };
+
+        let key = (credentials.trim().to_string(), hash.expose().to_string());
+        if self.verified.lock().unwrap().contains(&key) {
+            return true;
+        }
+        let hash = hash.expose().to_string();
+        let verified = tokio::task::spawn_blocking(move || bcrypt::verify(password, &hash))
+            .await
+            .is_ok_and(|verified| verified.unwrap_or(false));
+        if verified {
+            self.verified.lock().unwrap().insert(key);
+        }
+
+        verified
+    }
+
+    /// The `WWW-Authenticate` challenge for unauthorized requests.
+    pub fn challenge(web_config: &WebConfig) -> &'static str {
+        if web_config.basic_auth_users.is_empty() {
+            "Bearer"
+        } else {
+            "Basic"
+        }
+    }
+}
+
+/// Loads the certificate and key and reloads them whenever one of the files changes, so
+/// that renewed certificates are served without a restart. A failed reload keeps the
+/// previous certificate.
+pub async fn load_tls_config(tls_server_config: &TlsServerConfig) -> anyhow::Result<RustlsConfig> {
+    let TlsServerConfig {
+        cert_file,
+        key_file,
+    } = tls_server_config.clone();
+    let rustls_config = RustlsConfig::from_pem_file(&cert_file, &key_file)
+        .await
+        .context(format!(
+            "Failed to load the TLS certificate {} and key {}",
+            cert_file.display(),
+            key_file.display()
+        ))?;
+
+    let reloaded_config = rustls_config.clone();
+    tokio::spawn(async move {
+        let mut last_modified = (modified(&cert_file), modified(&key_file));
+        let mut interval = tokio::time::interval(TLS_RELOAD_INTERVAL);
+        loop {
+            interval.tick().await;
+            let current_modified = (modified(&cert_file), modified(&key_file));
+            if current_modified == last_modified {
+                continue;
+            }
+            last_modified = current_modified;
+
+            match reloaded_config
+                .reload_from_pem_file(&cert_file, &key_file)
+                .await
+            {
+                Ok(()) => info!("Reloaded the TLS certificate {}", cert_file.display()),
+                Err(e) => error!(
+                    "Failed to reload the TLS certificate {}, keeping the previous one: {e}",
+                    cert_file.display()
+                ),
+            }
+        }
+    });
+
+    Ok(rustls_config)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
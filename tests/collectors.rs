@@ -0,0 +1,379 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use prometheus::Registry;
+
+use hypurr_exporter::{
+    financial_meta::get_coingecko_data,
+    metrics::Metrics,
+    privacy::AddressMask,
+    probe::{ProbeModule, probe},
+    protocol_meta::{get_evm_data, get_protocol_data, get_validator_data},
+    snapshot::Snapshots,
+    transport::FixtureTransport,
+    user_details::{OpenOrderExposure, StakingReward, get_account_details, get_user_details},
+    vault_details::get_vault_details,
+};
+
+mod common;
+
+use common::{
+    USER_ADDRESS, empty_info_client, fixture_dir, fixture_http_client, fixture_info_client,
+    http_client, info_client,
+};
+
+const SUB_ACCOUNT_ADDRESS: &str = "0x035605fc2f24d65300227189025e90a0d947f16c";
+
+#[tokio::test]
+async fn vault_details_are_parsed() {
+    let info_client = fixture_info_client();
+
+    let (
+        value,
+        pnl,
+        apr,
+        leader_fraction,
+        leader_commission,
+        num_followers,
+        max_distributable,
+        max_withdrawable,
+        is_closed,
+        allow_deposits,
+    ) = get_vault_details(&info_client, "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303")
+        .await
+        .unwrap();
+
+    assert_eq!(value, 1_250_000.5);
+    assert_eq!(pnl, 2500.25);
+    assert_eq!(apr, 0.125);
+    assert_eq!(leader_fraction, 0.1);
+    assert_eq!(leader_commission, 0.1);
+    assert_eq!(num_followers, 2);
+    assert_eq!(max_distributable, 1_000_000.0);
+    assert_eq!(max_withdrawable, 250_000.0);
+    assert!(!is_closed);
+    assert!(allow_deposits);
+}
+
+#[tokio::test]
+async fn user_details_are_parsed() {
+    let info_client = fixture_info_client();
+
+    let user_details = get_user_details(&info_client, USER_ADDRESS.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(user_details.address, USER_ADDRESS);
+    assert_eq!(user_details.master, USER_ADDRESS);
+    assert_eq!(user_details.account_value, 12345.67);
+    assert_eq!(user_details.pnl, 345.67);
+    assert_eq!(user_details.staking_delegated, 1500.5);
+    assert_eq!(user_details.staking_undelegated, 200.0);
+    assert_eq!(user_details.staking_pending_withdrawal, 50.25);
+    assert_eq!(user_details.staking_num_pending_withdrawals, 2);
+    assert_eq!(
+        user_details.staking_rewards,
+        vec![
+            StakingReward {
+                source: "delegation".to_string(),
+                amount: 3.75
+            },
+            StakingReward {
+                source: "commission".to_string(),
+                amount: 0.75
+            },
+        ]
+    );
+    assert_eq!(user_details.staking_delegations.len(), 2);
+    assert_eq!(user_details.staking_delegations[0].amount, 1000.5);
+    assert_eq!(
+        user_details.staking_delegations[0].locked_until,
+        1735466781353
+    );
+
+    // The order with an empty size is counted but can't be valued.
+    assert_eq!(user_details.num_open_orders, 4);
+    assert_eq!(user_details.num_unparseable_open_orders, 1);
+    assert_eq!(user_details.value_open_orders, 45000.0 + 55000.0 + 250.0);
+    assert_eq!(
+        user_details.open_order_exposures,
+        vec![
+            OpenOrderExposure {
+                market: "perp".to_string(),
+                side: "buy".to_string(),
+                notional: 45000.0
+            },
+            OpenOrderExposure {
+                market: "perp".to_string(),
+                side: "sell".to_string(),
+                notional: 55000.0
+            },
+            OpenOrderExposure {
+                market: "spot".to_string(),
+                side: "sell".to_string(),
+                notional: 250.0
+            },
+        ]
+    );
+    assert_eq!(user_details.open_order_groups.len(), 4);
+    assert_eq!(
+        user_details
+            .open_order_groups
+            .iter()
+            .map(|group| group.count)
+            .sum::<usize>(),
+        user_details.num_open_orders
+    );
+    assert!(user_details.oldest_open_order_age > 0.0);
+
+    let metrics = Metrics::new().unwrap();
+    metrics.update_users(std::slice::from_ref(&user_details), true);
+    assert_eq!(
+        metrics
+            .user_staking_delegation_locked_until_timestamp_seconds
+            .with_label_values(&[
+                USER_ADDRESS,
+                USER_ADDRESS,
+                &user_details.staking_delegations[0].validator
+            ])
+            .get(),
+        1735466781.353
+    );
+}
+
+#[tokio::test]
+async fn sub_accounts_are_discovered() {
+    let info_client = fixture_info_client();
+
+    let account_details = get_account_details(&info_client, USER_ADDRESS.to_string())
+        .await
+        .unwrap();
+
+    assert!(account_details.is_complete());
+    assert_eq!(account_details.accounts.len(), 2);
+    assert_eq!(account_details.accounts[0].address, USER_ADDRESS);
+    assert_eq!(account_details.accounts[1].address, SUB_ACCOUNT_ADDRESS);
+    assert_eq!(account_details.accounts[1].master, USER_ADDRESS);
+}
+
+#[tokio::test]
+async fn failed_sub_account_discovery_is_reported() {
+    let mut transport = FixtureTransport::new();
+    for entry in std::fs::read_dir(fixture_dir()).unwrap() {
+        let path = entry.unwrap().path();
+        let key = path.file_stem().unwrap().to_str().unwrap().to_string();
+        if key != "subAccounts" {
+            transport = transport.with_fixture(key, std::fs::read_to_string(&path).unwrap());
+        }
+    }
+    let info_client = info_client(http_client(Arc::new(transport)));
+
+    let account_details = get_account_details(&info_client, USER_ADDRESS.to_string())
+        .await
+        .unwrap();
+
+    // The master account is still collected, but the totals would undercount.
+    assert!(!account_details.is_complete());
+    assert_eq!(account_details.accounts.len(), 1);
+    assert_eq!(account_details.errors[0].kind(), "http_status");
+}
+
+#[tokio::test]
+async fn protocol_data_is_parsed() {
+    let info_client = fixture_info_client();
+
+    let (af_account_value, num_spot_tokens, num_perp_tokens) =
+        get_protocol_data(&info_client).await.unwrap();
+
+    assert_eq!(af_account_value, 12345.67);
+    assert_eq!(num_spot_tokens, 3);
+    // Delisted perps aren't counted.
+    assert_eq!(num_perp_tokens, 2);
+}
+
+#[tokio::test]
+async fn validator_data_is_parsed() {
+    let info_client = fixture_info_client();
+
+    let validator_set = get_validator_data(&info_client).await.unwrap();
+
+    assert_eq!(validator_set.num_active, 1);
+    assert_eq!(validator_set.validators.len(), 2);
+    assert_eq!(validator_set.validators[0].stake, 1_000_000.0);
+    assert_eq!(validator_set.validators[0].commission, 0.05);
+    assert_eq!(
+        validator_set.validators[0].uptime_fraction,
+        vec![("day".to_string(), 1.0), ("week".to_string(), 0.998)]
+    );
+    assert!(validator_set.validators[1].is_jailed);
+}
+
+#[tokio::test]
+async fn evm_data_is_parsed() {
+    let http_client = fixture_http_client();
+
+    let (block_number, base_fee) =
+        get_evm_data(&http_client, "http://fixtures/evm", &HashMap::new())
+            .await
+            .unwrap();
+
+    assert_eq!(block_number, 0x6a1b2c);
+    assert_eq!(base_fee, 1_000_000_000);
+}
+
+#[tokio::test]
+async fn coingecko_data_is_parsed() {
+    let http_client = fixture_http_client();
+
+    let (price, marketcap, fdv, tvl, circulating_supply, total_supply) = get_coingecko_data(
+        &http_client,
+        "http://fixtures/api/v3/coins/hyperliquid",
+        "key",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(price, 25.5);
+    assert_eq!(marketcap, 8_500_000_000);
+    assert_eq!(fdv, 25_500_000_000);
+    assert_eq!(tvl, 2_000_000_000);
+    assert_eq!(circulating_supply, 333_928_180.0);
+    assert_eq!(total_supply, 999_834_331.0);
+}
+
+#[tokio::test]
+async fn missing_fixtures_are_reported_as_http_status_errors() {
+    let info_client = empty_info_client();
+
+    let error = get_vault_details(&info_client, "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303")
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.kind(), "http_status");
+}
+
+#[tokio::test]
+async fn probes_export_the_metrics_of_the_target() {
+    let info_client = fixture_info_client();
+
+    let metric_families = probe(
+        &info_client,
+        ProbeModule::Vault,
+        "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303",
+        &AddressMask::default(),
+        HashMap::new(),
+    )
+    .await
+    .unwrap();
+
+    let value = |name: &str| {
+        metric_families
+            .iter()
+            .find(|family| family.name() == name)
+            .map(|family| family.get_metric()[0].get_gauge().value())
+    };
+    assert_eq!(value("probe_success"), Some(1.0));
+    assert_eq!(value("hyperliquid_vault_value"), Some(1_250_000.5));
+    assert_eq!(value("hyperliquid_user_account_value"), None);
+}
+
+#[tokio::test]
+async fn user_probes_carry_the_labels_but_no_totals() {
+    let info_client = fixture_info_client();
+    let labels = HashMap::from([("network".to_string(), "testnet".to_string())]);
+
+    let metric_families = probe(
+        &info_client,
+        ProbeModule::User,
+        SUB_ACCOUNT_ADDRESS,
+        &AddressMask::default(),
+        labels,
+    )
+    .await
+    .unwrap();
+
+    let family = |name: &str| metric_families.iter().find(|family| family.name() == name);
+    let probe_success = &family("probe_success").unwrap().get_metric()[0];
+    assert_eq!(probe_success.get_gauge().value(), 1.0);
+    let label = &probe_success.get_label()[0];
+    assert_eq!((label.name(), label.value()), ("network", "testnet"));
+    assert!(family("hyperliquid_user_account_value").is_some());
+    assert!(family("hyperliquid_user_total_account_value").is_none());
+    assert!(family("hyperliquid_user_num_sub_accounts").is_none());
+}
+
+#[tokio::test]
+async fn vault_series_disappear_once_the_vault_is_removed() {
+    let info_client = fixture_info_client();
+    let metrics = Metrics::new().unwrap();
+    let registry = Registry::new();
+    metrics.register(&registry).unwrap();
+    let exported = || {
+        registry
+            .gather()
+            .iter()
+            .any(|family| family.name() == "vault_value")
+    };
+
+    let vault_details =
+        get_vault_details(&info_client, "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303")
+            .await
+            .unwrap();
+    metrics.update_vault(vault_details);
+    assert!(exported());
+
+    metrics.reset_vault();
+    assert!(!exported());
+}
+
+#[tokio::test]
+async fn snapshots_serve_the_latest_user_details() {
+    let info_client = fixture_info_client();
+    let mut snapshots = Snapshots::default();
+    assert!(
+        snapshots
+            .view(Duration::from_secs(300), &AddressMask::default())
+            .user
+            .stale
+    );
+
+    let account_details = get_account_details(&info_client, USER_ADDRESS.to_string())
+        .await
+        .unwrap();
+    snapshots.user.record_success(account_details.accounts);
+
+    let user = snapshots
+        .user(
+            &SUB_ACCOUNT_ADDRESS.to_uppercase(),
+            Duration::from_secs(300),
+            &AddressMask::default(),
+        )
+        .unwrap();
+    assert!(!user.stale);
+    let json = serde_json::to_value(&user.data).unwrap();
+    assert_eq!(json["master"], USER_ADDRESS);
+    assert!(json["staking_delegations"][0]["validator"].is_string());
+    assert!(json["staking_rewards"][0]["source"].is_string());
+    assert!(json["open_order_exposures"][0]["notional"].is_number());
+    assert!(
+        snapshots
+            .user(
+                "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303",
+                Duration::from_secs(300),
+                &AddressMask::default(),
+            )
+            .is_none()
+    );
+
+    let missing_fixture = get_vault_details(
+        &empty_info_client(),
+        "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303",
+    )
+    .await
+    .unwrap_err();
+    snapshots.user.record_error(&missing_fixture);
+
+    // The data of the last success is kept, but flagged as stale.
+    let view = snapshots.view(Duration::from_secs(300), &AddressMask::default());
+    assert!(view.user.stale);
+    assert_eq!(view.user.data.unwrap().len(), 2);
+}
@@ -0,0 +1,72 @@
+//! Helpers which are shared by the integration tests. Each test crate only uses some of
+//! them.
+#![allow(dead_code)]
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use hypurr_exporter::{
+    http_client::HttpClient,
+    info_client::InfoClient,
+    transport::{FixtureTransport, Transport},
+    utils::{CacheConfig, RateLimitConfig, RetryConfig},
+};
+
+pub const USER_ADDRESS: &str = "0x8c967e73e7b15087c42a10d344cff4c96d877f1d";
+
+pub fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+pub fn fixture_transport() -> FixtureTransport {
+    FixtureTransport::from_dir(fixture_dir()).unwrap()
+}
+
+/// An HTTP client with the default retries, rate limit and cache, which executes its
+/// requests with the transport.
+pub fn http_client(transport: Arc<dyn Transport>) -> HttpClient {
+    http_client_with(
+        transport,
+        &RetryConfig::default(),
+        &RateLimitConfig::default(),
+    )
+}
+
+/// An HTTP client like `http_client`, but with the given retries and rate limit.
+pub fn http_client_with(
+    transport: Arc<dyn Transport>,
+    retry_config: &RetryConfig,
+    rate_limit_config: &RateLimitConfig,
+) -> HttpClient {
+    HttpClient::with_transport(
+        reqwest::Client::new(),
+        transport,
+        retry_config,
+        rate_limit_config,
+        &CacheConfig::default(),
+    )
+    .unwrap()
+}
+
+pub fn info_client(http_client: HttpClient) -> InfoClient {
+    InfoClient::builder()
+        .base_url("http://fixtures/info")
+        .http_client(http_client)
+        .build()
+        .unwrap()
+}
+
+pub fn fixture_http_client() -> HttpClient {
+    http_client(Arc::new(fixture_transport()))
+}
+
+pub fn fixture_info_client() -> InfoClient {
+    info_client(fixture_http_client())
+}
+
+/// An info client without fixtures, whose requests all fail with a 404.
+pub fn empty_info_client() -> InfoClient {
+    info_client(http_client(Arc::new(FixtureTransport::new())))
+}
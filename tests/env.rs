@@ -0,0 +1,129 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use clap::Parser;
+use tokio::sync::{Mutex, MutexGuard};
+
+use hypurr_exporter::{
+    cli::Cli,
+    utils::{Config, ENV_PREFIX},
+};
+
+/// The tests of this file share the environment of the process, so they take turns.
+static ENV: Mutex<()> = Mutex::const_new(());
+
+/// Sets the `HYPURR_*` variables until it's dropped.
+struct Env {
+    names: Vec<String>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Env {
+    async fn set(vars: &[(&str, &str)]) -> Self {
+        let lock = ENV.lock().await;
+        let names: Vec<_> = vars
+            .iter()
+            .map(|(name, _)| format!("{ENV_PREFIX}{name}"))
+            .collect();
+        for (name, (_, value)) in names.iter().zip(vars) {
+            // SAFETY: The variables are only read and written while holding `ENV`.
+            unsafe { std::env::set_var(name, value) };
+        }
+
+        Self { names, _lock: lock }
+    }
+}
+
+impl Drop for Env {
+    fn drop(&mut self) {
+        for name in self.names.iter() {
+            // SAFETY: The variables are only read and written while holding `ENV`.
+            unsafe { std::env::remove_var(name) };
+        }
+    }
+}
+
+/// Writes a file into the temporary directory, named after the test to keep them apart.
+fn temp_file(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("hypurr-exporter-{}-{name}", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn default_config() -> Config {
+    toml::from_str("").unwrap()
+}
+
+#[tokio::test]
+async fn variables_win_over_their_file_variant() {
+    let key_file = temp_file("coingecko-key", "file-key\n");
+    let key_file = key_file.to_str().unwrap();
+
+    let mut config = default_config();
+    let env = Env::set(&[("COINGECKO_KEY_FILE", key_file)]).await;
+    config.apply_env_overrides().unwrap();
+    drop(env);
+    assert_eq!(config.coingecko_key.unwrap().expose(), "file-key");
+
+    let mut config = default_config();
+    let _env = Env::set(&[
+        ("COINGECKO_KEY", "env-key"),
+        ("COINGECKO_KEY_FILE", key_file),
+    ])
+    .await;
+    config.apply_env_overrides().unwrap();
+    assert_eq!(config.coingecko_key.unwrap().expose(), "env-key");
+}
+
+#[tokio::test]
+async fn lists_and_maps_are_parsed() {
+    let mut config = default_config();
+    let _env = Env::set(&[
+        ("RETRY_RETRYABLE_STATUSES", "429, 503"),
+        ("CACHE_TTL_SECS", "meta=300,spotMeta=300"),
+    ])
+    .await;
+
+    config.apply_env_overrides().unwrap();
+
+    assert_eq!(config.retry.retryable_statuses, vec![429, 503]);
+    assert_eq!(
+        config.cache.ttl_secs,
+        HashMap::from([("meta".to_string(), 300), ("spotMeta".to_string(), 300)])
+    );
+}
+
+#[tokio::test]
+async fn malformed_pairs_are_rejected() {
+    let mut config = default_config();
+    let _env = Env::set(&[("CACHE_TTL_SECS", "meta=300,spotMeta")]).await;
+
+    let error = config.apply_env_overrides().unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Invalid pair \"spotMeta\" of HYPURR_CACHE_TTL_SECS, expected key=value"
+    );
+}
+
+#[tokio::test]
+async fn flags_win_over_the_environment_which_wins_over_the_file() {
+    let config_file = temp_file(
+        "config.toml",
+        "listen_address = \"127.0.0.1:1\"\nmetrics_path = \"/file\"\nmax_concurrent_probes = 8\n",
+    );
+    let cli = Cli::parse_from([
+        "hypurr-exporter",
+        "--config",
+        config_file.to_str().unwrap(),
+        "--listen-address",
+        "127.0.0.1:3",
+    ]);
+    let _env = Env::set(&[("LISTEN_ADDRESS", "127.0.0.1:2"), ("METRICS_PATH", "/env")]).await;
+
+    let config = cli.read_config().await.unwrap();
+
+    assert_eq!(config.listen_address, "127.0.0.1:3");
+    assert_eq!(config.metrics_path, "/env");
+    assert_eq!(config.max_concurrent_probes, 8);
+    assert_eq!(config.snapshot_max_age_secs, 300);
+}
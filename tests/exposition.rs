@@ -0,0 +1,70 @@
+use prometheus::{IntCounterVec, Opts, Registry};
+
+use hypurr_exporter::exposition::{CreatedTimestamps, ExpositionFormat};
+
+#[test]
+fn exposition_format_is_negotiated_by_quality() {
+    let prometheus_accept = "application/openmetrics-text;version=1.0.0;q=0.5,application/openmetrics-text;version=0.0.1;q=0.4,text/plain;version=0.0.4;q=0.3,*/*;q=0.2";
+
+    assert_eq!(
+        ExpositionFormat::negotiate(Some(prometheus_accept)),
+        ExpositionFormat::OpenMetrics
+    );
+    assert_eq!(
+        ExpositionFormat::negotiate(Some(&format!(
+            "application/vnd.google.protobuf;proto=io.prometheus.client.MetricFamily;encoding=delimited;q=0.6,{prometheus_accept}"
+        ))),
+        ExpositionFormat::Protobuf
+    );
+    assert_eq!(
+        ExpositionFormat::negotiate(Some("application/openmetrics-text;version=2.0.0")),
+        ExpositionFormat::Text
+    );
+    assert_eq!(ExpositionFormat::negotiate(None), ExpositionFormat::Text);
+}
+
+#[test]
+fn open_metrics_are_encoded_with_created_timestamps() {
+    let registry = Registry::new();
+    let counter = IntCounterVec::new(
+        Opts::new("throttled_seconds_total", "Time \"throttled\""),
+        &["upstream"],
+    )
+    .unwrap();
+    registry.register(Box::new(counter.clone())).unwrap();
+    counter.with_label_values(&["info"]).inc();
+    let created_timestamps = CreatedTimestamps::new();
+
+    let encode = || {
+        String::from_utf8(
+            ExpositionFormat::OpenMetrics
+                .encode(&registry.gather(), &created_timestamps)
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    let created = |encoded: &str| {
+        encoded
+            .lines()
+            .find_map(|line| line.strip_prefix("throttled_seconds_created{upstream=\"info\"} "))
+            .unwrap()
+            .parse::<f64>()
+            .unwrap()
+    };
+
+    let encoded = encode();
+    assert!(encoded.starts_with(
+        "# TYPE throttled_seconds counter\n# UNIT throttled_seconds seconds\n# HELP throttled_seconds Time \\\"throttled\\\"\n"
+    ));
+    assert!(encoded.contains("throttled_seconds_total{upstream=\"info\"} 1.0\n"));
+    assert!(encoded.ends_with("# EOF\n"));
+
+    // The created timestamp is kept while the counter counts up and renewed on a reset.
+    counter.with_label_values(&["info"]).inc();
+    let first_created = created(&encoded);
+    let encoded = encode();
+    assert_eq!(created(&encoded), first_created);
+    counter.reset();
+    counter.with_label_values(&["info"]).inc();
+    assert!(created(&encode()) > first_created);
+}
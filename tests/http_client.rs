@@ -0,0 +1,209 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{TimeDelta, Utc};
+use reqwest::{Request, Response, StatusCode, header::RETRY_AFTER};
+use tokio::time::Instant;
+
+use hypurr_exporter::{
+    financial_meta::get_coingecko_data,
+    rate_limiter::RateLimiter,
+    single_flight::SingleFlight,
+    transport::{FixtureTransport, Transport, TransportFuture},
+    utils::{RateLimitConfig, RetryConfig},
+};
+
+mod common;
+
+use common::{USER_ADDRESS, fixture_transport, http_client, http_client_with, info_client};
+
+/// Answers with the scripted statuses and `Retry-After` headers first and with the
+/// fixtures afterwards, counting all requests.
+#[derive(Debug)]
+struct ScriptedTransport {
+    statuses: Mutex<VecDeque<(StatusCode, Option<String>)>>,
+    fixtures: FixtureTransport,
+    requests: AtomicUsize,
+}
+
+impl ScriptedTransport {
+    fn new(statuses: impl IntoIterator<Item = (StatusCode, Option<String>)>) -> Arc<Self> {
+        Arc::new(Self {
+            statuses: Mutex::new(statuses.into_iter().collect()),
+            fixtures: fixture_transport(),
+            requests: AtomicUsize::new(0),
+        })
+    }
+
+    fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+impl Transport for ScriptedTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let Some((status, retry_after)) = self.statuses.lock().unwrap().pop_front() else {
+            return self.fixtures.execute(request);
+        };
+
+        let mut response = http::Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            response = response.header(RETRY_AFTER, retry_after);
+        }
+        let response = response.body(String::new()).unwrap();
+
+        Box::pin(async move { Ok(Response::from(response)) })
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_stop_after_the_configured_attempts() {
+    let transport = ScriptedTransport::new(vec![(StatusCode::BAD_GATEWAY, None); 5]);
+    let retry_config = RetryConfig {
+        attempts: 3,
+        ..RetryConfig::default()
+    };
+    let info_client = info_client(http_client_with(
+        transport.clone(),
+        &retry_config,
+        &RateLimitConfig::default(),
+    ));
+    let start = Instant::now();
+
+    let error = info_client.meta().await.unwrap_err();
+
+    assert_eq!(error.kind(), "http_status");
+    assert_eq!(transport.requests(), 3);
+    // The backoff of the two retries is jittered between half and the full delay.
+    let max_backoff = Duration::from_millis(retry_config.base_delay_ms * 3);
+    assert!(start.elapsed() >= max_backoff / 2);
+    assert!(start.elapsed() <= max_backoff);
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_after_is_honored_in_seconds_and_as_http_date() {
+    // The date has no fractions of a second, so it's more than 6 seconds ahead.
+    let http_date = (Utc::now() + TimeDelta::seconds(7))
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string();
+
+    for retry_after in ["5".to_string(), http_date] {
+        let transport =
+            ScriptedTransport::new([(StatusCode::TOO_MANY_REQUESTS, Some(retry_after.clone()))]);
+        let info_client = info_client(http_client(transport.clone()));
+        let start = Instant::now();
+
+        info_client.meta().await.unwrap();
+
+        assert_eq!(transport.requests(), 2, "{retry_after}");
+        assert!(start.elapsed() >= Duration::from_secs(5), "{retry_after}");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_after_beyond_the_maximum_delay_fails_the_request() {
+    let transport = ScriptedTransport::new([(StatusCode::SERVICE_UNAVAILABLE, Some("60".into()))]);
+    let info_client = info_client(http_client(transport.clone()));
+
+    let error = info_client.meta().await.unwrap_err();
+
+    assert_eq!(error.kind(), "http_status");
+    assert_eq!(transport.requests(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_rate_limiter_throttles_once_the_bucket_is_empty() {
+    let rate_limiter = RateLimiter::new(&RateLimitConfig {
+        weight_per_minute: 60,
+    })
+    .unwrap();
+    let start = Instant::now();
+
+    rate_limiter.acquire(60).await;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // The bucket refills with one weight per second.
+    rate_limiter.acquire(30).await;
+    assert_eq!(start.elapsed().as_secs_f64().round(), 30.0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn retried_info_requests_acquire_their_weight_per_attempt() {
+    let transport = ScriptedTransport::new([(StatusCode::SERVICE_UNAVAILABLE, Some("0".into()))]);
+    let info_client = info_client(http_client_with(
+        transport.clone(),
+        &RetryConfig::default(),
+        &RateLimitConfig {
+            weight_per_minute: 20,
+        },
+    ));
+    let start = Instant::now();
+
+    // The portfolio request weighs 20, so the retry waits for the bucket to refill.
+    info_client.portfolio(USER_ADDRESS).await.unwrap();
+    assert_eq!(transport.requests(), 2);
+    assert_eq!(start.elapsed().as_secs_f64().round(), 60.0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn responses_are_cached_until_their_ttl_expires() {
+    let transport = ScriptedTransport::new([]);
+    let info_client = info_client(http_client(transport.clone()));
+
+    info_client.meta().await.unwrap();
+    info_client.meta().await.unwrap();
+    assert_eq!(transport.requests(), 1);
+
+    tokio::time::advance(Duration::from_secs(301)).await;
+    info_client.meta().await.unwrap();
+    assert_eq!(transport.requests(), 2);
+
+    // Request types without a TTL are sent every time.
+    info_client.portfolio(USER_ADDRESS).await.unwrap();
+    info_client.portfolio(USER_ADDRESS).await.unwrap();
+    assert_eq!(transport.requests(), 4);
+
+    for _ in 0..2 {
+        get_coingecko_data(
+            info_client.http_client(),
+            "http://fixtures/hyperliquid",
+            "key",
+        )
+        .await
+        .unwrap();
+    }
+    assert_eq!(transport.requests(), 5);
+}
+
+#[tokio::test(start_paused = true)]
+async fn identical_concurrent_requests_are_coalesced() {
+    let single_flight = SingleFlight::default();
+    let calls = &AtomicUsize::new(0);
+    let call = move || async move {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok("body".to_string())
+    };
+
+    let (first, second, other) = tokio::join!(
+        single_flight.run("meta", call),
+        single_flight.run("meta", call),
+        single_flight.run("spotMeta", call),
+    );
+
+    assert_eq!(first.unwrap(), "body");
+    assert_eq!(second.unwrap(), "body");
+    assert_eq!(other.unwrap(), "body");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Once the flight landed, the next call starts a new one.
+    single_flight.run("meta", call).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
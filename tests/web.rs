@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use hypurr_exporter::{privacy::Secret, utils::WebConfig, web::Authenticator};
+
+#[tokio::test]
+async fn requests_are_authorized_by_basic_auth_or_bearer_token() {
+    let authenticator = Authenticator::new();
+    let mut web_config = WebConfig::default();
+    assert!(authenticator.is_authorized(&web_config, None).await);
+
+    web_config.basic_auth_users = HashMap::from([(
+        "prometheus".to_string(),
+        Secret::new(bcrypt::hash("secret", 4).unwrap()),
+    )]);
+    web_config.bearer_tokens = vec![Secret::new("token")];
+
+    // "prometheus:secret", "prometheus:wrong" and "grafana:secret" in base64.
+    for (authorization, authorized) in [
+        (None, false),
+        (Some("Basic cHJvbWV0aGV1czpzZWNyZXQ="), true),
+        (Some("Basic cHJvbWV0aGV1czpzZWNyZXQ="), true),
+        (Some("Basic cHJvbWV0aGV1czp3cm9uZw=="), false),
+        (Some("Basic Z3JhZmFuYTpzZWNyZXQ="), false),
+        (Some("Bearer token"), true),
+        (Some("Bearer tok"), false),
+        (Some("Token token"), false),
+    ] {
+        assert_eq!(
+            authenticator
+                .is_authorized(&web_config, authorization)
+                .await,
+            authorized,
+            "{authorization:?}"
+        );
+    }
+}